@@ -0,0 +1,170 @@
+use std::{fmt, num::NonZeroU32, str::FromStr};
+
+use personal_finance::balance::Rounding;
+
+/// A currency identified by its three letter ISO 4217 code, e.g. USD or EUR
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    /// Create a currency from a three letter code
+    ///
+    /// The code is case insensitive and stored in uppercase.
+    pub fn new<T: AsRef<str>>(code: T) -> Option<Self> {
+        match code.as_ref().trim().as_bytes() {
+            [a, b, c] if [a, b, c].iter().all(|x| x.is_ascii_alphabetic()) => Some(Self([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("currency code is always ascii")
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Currency::new(s).ok_or(ParseError)
+    }
+}
+
+/// The rate used to exchange one currency into another
+///
+/// The rate is a ratio where `per` units of `from` are worth `rate` units of `to`.
+///
+/// # Examples
+/// ```
+/// use std::num::NonZeroU32;
+/// use cqrs::currency::{Currency, ExchangeRate};
+///
+/// // 1 USD = 10.45 SEK
+/// let usd_to_sek = ExchangeRate::new(
+///     Currency::new("USD").unwrap(),
+///     Currency::new("SEK").unwrap(),
+///     1045,
+///     NonZeroU32::new(100).unwrap(),
+/// );
+///
+/// assert_eq!(usd_to_sek.convert(200), Some(2090));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExchangeRate {
+    from: Currency,
+    to: Currency,
+    rate: u32,
+    per: NonZeroU32,
+}
+
+impl ExchangeRate {
+    pub fn new(from: Currency, to: Currency, rate: u32, per: NonZeroU32) -> Self {
+        Self {
+            from,
+            to,
+            rate,
+            per,
+        }
+    }
+
+    pub fn from(&self) -> Currency {
+        self.from
+    }
+
+    pub fn to(&self) -> Currency {
+        self.to
+    }
+
+    /// Convert an amount in `from` currency into `to` currency
+    ///
+    /// The result is rounded with the default [Rounding], see [ExchangeRate::convert_with].
+    pub fn convert(&self, amount: u32) -> Option<u32> {
+        self.convert_with(amount, Rounding::default())
+    }
+
+    /// Convert an amount in `from` currency into `to` currency
+    ///
+    /// Returns None if the converted amount doesn't fit in a u32.
+    pub fn convert_with(&self, amount: u32, rounding: Rounding) -> Option<u32> {
+        let scaled = u64::from(amount) * u64::from(self.rate);
+        u32::try_from(rounding.divide(scaled, u64::from(self.per.get()))).ok()
+    }
+}
+
+impl From<Currency> for String {
+    fn from(value: Currency) -> Self {
+        value.as_str().to_owned()
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParseError;
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Unable to parse currency")
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_case::test_case;
+
+    #[test_case("USD" => Some(String::from("USD")))]
+    #[test_case("eur" => Some(String::from("EUR")))]
+    #[test_case(" sek " => Some(String::from("SEK")))]
+    #[test_case("US" => None)]
+    #[test_case("U5D" => None)]
+    fn currency_new(input: &str) -> Option<String> {
+        Currency::new(input).map(|x| x.to_string())
+    }
+
+    fn usd_to_sek(rate: u32) -> ExchangeRate {
+        ExchangeRate::new(
+            Currency::new("USD").unwrap(),
+            Currency::new("SEK").unwrap(),
+            rate,
+            NonZeroU32::new(100).unwrap(),
+        )
+    }
+
+    #[test_case(1045, 100 => Some(1045))]
+    #[test_case(1045, 3 => Some(31))]
+    #[test_case(1050, 1 => Some(11))]
+    #[test_case(1045, u32::MAX => None)]
+    fn exchange_rate_convert(rate: u32, amount: u32) -> Option<u32> {
+        usd_to_sek(rate).convert(amount)
+    }
+
+    #[test_case(Rounding::HalfUp => Some(11))]
+    #[test_case(Rounding::HalfEven => Some(10))]
+    #[test_case(Rounding::Floor => Some(10))]
+    #[test_case(Rounding::Ceil => Some(11))]
+    fn exchange_rate_convert_with(rounding: Rounding) -> Option<u32> {
+        usd_to_sek(1050).convert_with(1, rounding)
+    }
+}
@@ -1,7 +1,9 @@
-use std::fmt;
-
 use thiserror::Error;
 
+use personal_finance::error::AmountError;
+
+use crate::{write::ledger::LedgerId, Number};
+
 #[derive(Debug, PartialEq, Eq, Error)]
 pub enum AccountError {
     #[error("Account '{0}' has already been opened.")]
@@ -10,14 +12,20 @@ pub enum AccountError {
     Closed,
     #[error("Account doesn't exist.")]
     NotExist,
+    #[error("Account still has a balance of {0}.")]
+    NonZeroBalance(i64),
     #[error("That ledger doesn't exist")]
     LedgerDoesnExist,
+    #[error(transparent)]
+    Store(#[from] StoreError),
 }
 
 #[derive(Debug, PartialEq, Eq, Error)]
 pub enum LedgerError {
     #[error("The ledger already exists")]
     AlreadyExists,
+    #[error(transparent)]
+    Store(#[from] StoreError),
 }
 
 #[non_exhaustive]
@@ -31,4 +39,76 @@ pub enum TransactionError {
     AccountDoesntExist,
     #[error("That ledger doesn't exist")]
     LedgerDoesnExist,
+    #[error("The period of the transaction has been sealed")]
+    PeriodSealed,
+    #[error("All postings of a transaction must be in the same currency")]
+    MixedCurrencies,
+    #[error("The amount could not be exchanged")]
+    InvalidExchange,
+    #[error("The transaction is dated too far into the future")]
+    FutureDated,
+    #[error("Account '{0}' is both debited and credited in the same transaction")]
+    SelfOffsetting(Number),
+    #[error(transparent)]
+    Amount(#[from] AmountError),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+}
+
+/// A storage failed to append events, none of the events were stored
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum StoreError {
+    #[error("The store rejected the events: {0}")]
+    Rejected(String),
+    #[error("The store has {actual} events but the command expected {expected}")]
+    Conflict { expected: usize, actual: usize },
+    #[error("The store is not available")]
+    Unavailable,
+}
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum BuildError {
+    #[error("'{0}' is not a valid ledger id")]
+    InvalidLedger(String),
+    #[error("'{0}' is not a valid account number")]
+    InvalidNumber(u32),
+    #[error("'{0}' is not a valid account name")]
+    InvalidName(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum HistoryError {
+    #[error("Ledger '{ledger:?}' at position {position} has already been created")]
+    DuplicateLedger { position: usize, ledger: LedgerId },
+    #[error("Ledger '{ledger:?}' at position {position} has not been created")]
+    LedgerNotCreated { position: usize, ledger: LedgerId },
+    #[error("Account '{account}' at position {position} is not open")]
+    AccountNotOpen { position: usize, account: Number },
+    #[error("The transaction at position {position} does not balance")]
+    ImbalancedTransaction { position: usize },
+}
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum LifecycleError {
+    #[error("Account '{account}' at position {position} is opened while already open")]
+    AlreadyOpen { position: usize, account: Number },
+    #[error("Account '{account}' at position {position} is closed without being open")]
+    CloseWithoutOpen { position: usize, account: Number },
+    #[error("Account '{account}' at position {position} is posted to before it was opened")]
+    PostedBeforeOpen { position: usize, account: Number },
+    #[error("Account '{account}' at position {position} is posted to while closed")]
+    PostedWhileClosed { position: usize, account: Number },
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Unable to read events")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid event on line {line}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
 }
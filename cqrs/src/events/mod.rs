@@ -1,19 +1,31 @@
-use std::{sync::Arc, ops::Deref};
+//! The events that make up the history of every ledger.
+//!
+//! There is one event vocabulary and every event except `LedgerCreated` names
+//! the ledger it belongs to, so many ledgers can share one store:
+//!
+//! - `LedgerCreated` starts a ledger, events before it are ignored by that ledger.
+//! - `AccountOpened`, `AccountClosed` and `AccountRecategorized` change the chart
+//!   of accounts.
+//! - `Transaction` posts a balanced set of [Posting]s on a date.
+//! - `PeriodSealed` stops new transactions up to and including a date.
+//! - `CommandRejected` records a failed command for auditing and changes nothing.
 
-use super::JournalId;
-use crate::write::ledger::LedgerId;
+use std::{collections::BTreeMap, ops::Deref, sync::Arc};
+
+use crate::{error::BuildError, write::ledger::LedgerId, Posting};
 use chrono::prelude::*;
-use personal_finance::{
-    account::{Category, Name, Number},
-    balance::Balance,
-};
+use personal_finance::account::{Category, Name, Number};
 
 pub mod projections;
 pub mod store;
+pub mod validate;
+
+pub use validate::validate_history;
 
 pub type EventPointerType = <Event as EventPointer>::Pointer<Event>;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Event {
     LedgerCreated {
         id: LedgerId,
@@ -28,14 +40,147 @@ pub enum Event {
         ledger: LedgerId,
         account: Number,
     },
+    AccountRecategorized {
+        ledger: LedgerId,
+        account: Number,
+        category: Category,
+    },
     Transaction {
         ledger: LedgerId,
         description: String,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_date"))]
         date: Date<Utc>,
-        transactions: Vec<(Number, Balance)>,
+        transactions: Vec<Posting>,
+        /// Free-form tags such as a project code or client id
+        #[cfg_attr(feature = "serde", serde(default))]
+        metadata: BTreeMap<String, String>,
+    },
+    PeriodSealed {
+        ledger: LedgerId,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_date"))]
+        up_to: Date<Utc>,
+    },
+    /// A command that failed, kept for the audit trail
+    ///
+    /// This doesn't change the state of the ledger and is ignored when replaying.
+    CommandRejected {
+        ledger: LedgerId,
+        reason: String,
     },
 }
 
+impl Event {
+    /// Create an `AccountOpened` event from primitive values
+    ///
+    /// Returns None if `id` or `name` isn't a valid account number or name.
+    ///
+    /// # Examples
+    /// ```
+    /// use cqrs::{write::ledger::LedgerId, Category, Event};
+    ///
+    /// let ledger = LedgerId::new("2014").unwrap();
+    /// assert!(Event::account_opened(ledger.clone(), 101, "Bank account", Category::Asset).is_some());
+    /// assert!(Event::account_opened(ledger, 0, "Bank account", Category::Asset).is_none());
+    /// ```
+    pub fn account_opened(
+        ledger: LedgerId,
+        id: u32,
+        name: &str,
+        category: Category,
+    ) -> Option<Self> {
+        Some(Self::AccountOpened {
+            ledger,
+            id: Number::new(id)?,
+            name: Name::new(name)?,
+            category,
+        })
+    }
+
+    pub fn try_ledger_created(ledger: &str) -> Result<Self, BuildError> {
+        Ok(Self::LedgerCreated {
+            id: LedgerId::parse(ledger)?,
+        })
+    }
+
+    /// Create an `AccountOpened` event validating all values
+    ///
+    /// # Examples
+    /// ```
+    /// use cqrs::{error::BuildError, Category, Event};
+    ///
+    /// assert!(Event::try_account_opened("2014", 101, "Bank account", Category::Asset).is_ok());
+    /// assert_eq!(
+    ///     Event::try_account_opened("2014", 0, "Bank account", Category::Asset),
+    ///     Err(BuildError::InvalidNumber(0))
+    /// );
+    /// ```
+    pub fn try_account_opened(
+        ledger: &str,
+        id: u32,
+        name: &str,
+        category: Category,
+    ) -> Result<Self, BuildError> {
+        Ok(Self::AccountOpened {
+            ledger: LedgerId::parse(ledger)?,
+            id: parse_number(id)?,
+            name: Name::new(name).ok_or_else(|| BuildError::InvalidName(name.to_owned()))?,
+            category,
+        })
+    }
+
+    pub fn try_account_closed(ledger: &str, account: u32) -> Result<Self, BuildError> {
+        Ok(Self::AccountClosed {
+            ledger: LedgerId::parse(ledger)?,
+            account: parse_number(account)?,
+        })
+    }
+
+    /// Sort the postings of a transaction so the order they were entered in doesn't matter
+    ///
+    /// Postings are ordered by account, then debits before credits and then by
+    /// amount. Other events are returned unchanged.
+    pub fn normalized(mut self) -> Self {
+        if let Self::Transaction { transactions, .. } = &mut self {
+            transactions.sort();
+        }
+
+        self
+    }
+
+    /// Returns true if both events are equal once normalized, see [Event::normalized]
+    pub fn equivalent(&self, other: &Event) -> bool {
+        self.clone().normalized() == other.clone().normalized()
+    }
+
+    /// The ledger the event belongs to
+    pub fn ledger(&self) -> Option<&LedgerId> {
+        match self {
+            Self::LedgerCreated { id } => Some(id),
+            Self::AccountOpened { ledger, .. }
+            | Self::AccountClosed { ledger, .. }
+            | Self::AccountRecategorized { ledger, .. }
+            | Self::Transaction { ledger, .. }
+            | Self::PeriodSealed { ledger, .. }
+            | Self::CommandRejected { ledger, .. } => Some(ledger),
+        }
+    }
+}
+
+/// Keep only the events belonging to the ledger `id`, see [Event::ledger]
+pub fn for_ledger<'a, I>(events: I, id: &'a LedgerId) -> impl Iterator<Item = &'a Event>
+where
+    I: IntoIterator<Item = &'a Event>,
+    I::IntoIter: 'a,
+{
+    events
+        .into_iter()
+        .filter(move |event| event.ledger() == Some(id))
+}
+
+fn parse_number(number: u32) -> Result<Number, BuildError> {
+    Number::new(number).ok_or(BuildError::InvalidNumber(number))
+}
+
 pub trait EventPointer {
     type Pointer<T>: Deref<Target = T>;
 
@@ -49,3 +194,97 @@ impl EventPointer for Event {
         Arc::new(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_case::test_case;
+
+    #[test]
+    fn try_account_opened_with_valid_values() {
+        let actual = Event::try_account_opened("2014", 101, "Bank account", Category::Asset);
+
+        assert_eq!(
+            actual,
+            Ok(Event::AccountOpened {
+                ledger: LedgerId::new("2014").unwrap(),
+                id: Number::new(101).unwrap(),
+                name: Name::new("Bank account").unwrap(),
+                category: Category::Asset,
+            })
+        );
+    }
+
+    #[test_case("-2014", 101, "Bank account" => BuildError::InvalidLedger(String::from("-2014")) ; "invalid ledger")]
+    #[test_case("2014", 0, "Bank account" => BuildError::InvalidNumber(0) ; "invalid number")]
+    #[test_case("2014", 101, "  " => BuildError::InvalidName(String::from("  ")) ; "invalid name")]
+    fn try_account_opened_with_invalid_value(ledger: &str, id: u32, name: &str) -> BuildError {
+        Event::try_account_opened(ledger, id, name, Category::Asset).unwrap_err()
+    }
+
+    #[test]
+    fn try_ledger_created_should_trim_ledger_id() {
+        assert_eq!(
+            Event::try_ledger_created(" 2014-q2 "),
+            Ok(Event::LedgerCreated {
+                id: LedgerId::new("2014-q2").unwrap()
+            })
+        );
+    }
+
+    #[test]
+    fn try_ledger_created_and_account_closed() {
+        assert!(Event::try_ledger_created("2014").is_ok());
+        assert_eq!(
+            Event::try_ledger_created(""),
+            Err(BuildError::InvalidLedger(String::new()))
+        );
+        assert!(Event::try_account_closed("2014", 101).is_ok());
+        assert_eq!(
+            Event::try_account_closed("2014", 0),
+            Err(BuildError::InvalidNumber(0))
+        );
+    }
+
+    #[test]
+    fn for_ledger_should_only_yield_events_of_that_ledger() {
+        let first = LedgerId::new("2014").unwrap();
+        let second = LedgerId::new("2015").unwrap();
+        let events = [
+            Event::LedgerCreated { id: first.clone() },
+            Event::LedgerCreated { id: second.clone() },
+            Event::account_opened(first.clone(), 101, "Bank", Category::Asset).unwrap(),
+            Event::account_opened(second.clone(), 101, "Bank", Category::Asset).unwrap(),
+            Event::AccountClosed {
+                ledger: second,
+                account: Number::new(101).unwrap(),
+            },
+        ];
+
+        let actual = for_ledger(&events, &first).collect::<Vec<_>>();
+
+        assert_eq!(actual, vec![&events[0], &events[2]]);
+    }
+
+    #[test]
+    fn reordered_transactions_should_normalize_to_equal_events() {
+        let transaction = |postings: &[Posting]| Event::Transaction {
+            ledger: LedgerId::new("2014").unwrap(),
+            description: String::from("Groceries"),
+            date: Utc.ymd(2014, 4, 1),
+            transactions: postings.to_vec(),
+            metadata: BTreeMap::new(),
+        };
+        let bank = Posting::credit(Number::new(101).unwrap(), 150).unwrap();
+        let food = Posting::debit(Number::new(501).unwrap(), 100).unwrap();
+        let drinks = Posting::debit(Number::new(501).unwrap(), 50).unwrap();
+
+        let first = transaction(&[bank, food, drinks]);
+        let second = transaction(&[drinks, bank, food]);
+
+        assert_ne!(first, second);
+        assert!(first.equivalent(&second));
+        assert_eq!(first.normalized(), transaction(&[bank, drinks, food]));
+    }
+}
@@ -1,5 +1,7 @@
 use std::marker::PhantomData;
 
+use super::store::EventStorage;
+
 pub struct Projection<S, E, F>
 where
     F: Fn(S, &E) -> S,
@@ -28,4 +30,141 @@ where
     {
         iter.fold(self.init.clone(), &self.update)
     }
+
+    /// Project the events until `stop` returns true for the state
+    ///
+    /// The state is checked before each event is consumed so no events are
+    /// pulled from the iterator after `stop` is satisfied.
+    pub fn project_until<'a, I, P>(&'a self, iter: I, stop: P) -> S
+    where
+        I: Iterator<Item = &'a E>,
+        P: Fn(&S) -> bool,
+        S: Clone,
+    {
+        let mut state = self.init.clone();
+        for event in iter {
+            if stop(&state) {
+                break;
+            }
+            state = (self.update)(state, event);
+        }
+
+        state
+    }
+}
+
+/// A projection that is only recomputed when the events of the store have changed
+///
+/// The cached state is reused as long as the store has the same
+/// [generation](EventStorage::generation) and length, so clearing the store or
+/// asking for the value of another store projects the events again.
+pub struct CachedProjection<S, P> {
+    projection: P,
+    cache: Option<((u64, usize), S)>,
+}
+
+impl<S, E, F> CachedProjection<S, Projection<S, E, F>>
+where
+    F: Fn(S, &E) -> S,
+    S: Clone,
+{
+    pub fn new(projection: Projection<S, E, F>) -> Self {
+        Self {
+            projection,
+            cache: None,
+        }
+    }
+
+    /// The projected state of all events in the store
+    pub fn value<T>(&mut self, store: &T) -> &S
+    where
+        T: EventStorage<E>,
+    {
+        let events = store.all();
+        let version = (store.generation(), events.len());
+        let is_stale = !matches!(&self.cache, Some((cached, _)) if *cached == version);
+        if is_stale {
+            let state = self.projection.project(events.iter());
+            self.cache = Some((version, state));
+        }
+
+        &self.cache.as_ref().expect("cache was just filled").1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use crate::events::store::InMemoryStore;
+
+    #[test]
+    fn project_until_should_stop_consuming_events() {
+        let events = [10, 20, 30, 40, 50];
+        let consumed = Cell::new(0);
+        let projection = Projection::new(0, |acc, x: &u32| acc + x);
+
+        let iter = events.iter().inspect(|_| consumed.set(consumed.get() + 1));
+        let actual = projection.project_until(iter, |acc| *acc >= 30);
+
+        assert_eq!(actual, 30);
+        assert_eq!(consumed.get(), 3);
+    }
+
+    #[test]
+    fn project_until_never_stopping_should_equal_project() {
+        let events = [10, 20, 30];
+        let projection = Projection::new(0, |acc, x: &u32| acc + x);
+
+        let actual = projection.project_until(events.iter(), |_| false);
+
+        assert_eq!(actual, projection.project(events.iter()));
+    }
+
+    #[test]
+    fn cached_projection_should_only_project_new_versions() {
+        let updates = Cell::new(0);
+        let mut store = InMemoryStore::new();
+        store.append(10u32);
+        store.append(20);
+        let mut cached = CachedProjection::new(Projection::new(0, |acc, x: &u32| {
+            updates.set(updates.get() + 1);
+            acc + x
+        }));
+
+        assert_eq!(*cached.value(&store), 30);
+        assert_eq!(*cached.value(&store), 30);
+        assert_eq!(updates.get(), 2);
+
+        store.append(30);
+        assert_eq!(*cached.value(&store), 60);
+        assert_eq!(updates.get(), 5);
+    }
+
+    #[test]
+    fn cached_projection_should_notice_a_cleared_store() {
+        let mut store = InMemoryStore::new();
+        store.append_many([10u32, 20]);
+        let mut cached = CachedProjection::new(Projection::new(0, |acc, x: &u32| acc + x));
+        assert_eq!(*cached.value(&store), 30);
+
+        store.clear();
+        store.append_many([1, 2]);
+
+        assert_eq!(*cached.value(&store), 3);
+    }
+
+    #[test]
+    fn cached_projection_should_notice_another_store() {
+        let mut first = InMemoryStore::new();
+        first.append_many([10u32, 20]);
+        let mut second = InMemoryStore::new();
+        second.append_many([1, 2]);
+        let mut cached = CachedProjection::new(Projection::new(0, |acc, x: &u32| acc + x));
+
+        assert_eq!(*cached.value(&first), 30);
+        assert_eq!(*cached.value(&second), 3);
+    }
 }
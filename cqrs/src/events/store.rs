@@ -1,8 +1,179 @@
+#[cfg(feature = "serde")]
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::{
+    future::Future,
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+#[cfg(feature = "serde")]
+use crate::error::ImportError;
+use crate::error::StoreError;
+
+pub use diff::{diff, Divergence};
 pub use in_memory_store::InMemoryStore;
+pub use read_only_store::ReadOnlyStore;
+pub use store_proxy::StoreProxy;
+pub use tee_store::TeeStore;
 
+pub mod diff;
 pub mod in_memory_store;
+pub mod read_only_store;
+pub mod store_proxy;
+pub mod tee_store;
 
 pub trait EventStorage<T> {
-    fn append(&mut self, event: T);
+    /// Append an event and return its position in the storage
+    fn append(&mut self, event: T) -> usize;
     fn all(&self) -> &[T];
+
+    /// Append events in order and return the range of positions they were given
+    fn append_many<I>(&mut self, events: I) -> Range<usize>
+    where
+        I: IntoIterator<Item = T>,
+        Self: Sized,
+    {
+        let start = self.all().len();
+        let end = events
+            .into_iter()
+            .map(|event| self.append(event) + 1)
+            .last()
+            .unwrap_or(start);
+
+        start..end
+    }
+
+    /// Append all events or none of them
+    ///
+    /// Storages that can fail part way should override this, the default appends
+    /// the events one by one with [EventStorage::append_many] and never fails.
+    fn append_all(&mut self, events: &[T]) -> Result<(), StoreError>
+    where
+        T: Clone,
+        Self: Sized,
+    {
+        self.append_many(events.iter().cloned());
+        Ok(())
+    }
+
+    /// Append all events or none of them without blocking an async runtime
+    ///
+    /// The default calls [EventStorage::append_all].
+    fn append_all_async(
+        &mut self,
+        events: &[T],
+    ) -> impl Future<Output = Result<(), StoreError>> + Send
+    where
+        T: Clone,
+        Self: Sized + Send,
+    {
+        std::future::ready(self.append_all(events))
+    }
+
+    /// Catch up on events appended by someone else
+    ///
+    /// This is for storages that cache a shared history, like [StoreProxy].
+    /// The default does nothing.
+    fn refresh(&mut self) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    /// Catch up on events appended by someone else without blocking an async runtime
+    ///
+    /// The default calls [EventStorage::refresh].
+    fn refresh_async(&mut self) -> impl Future<Output = Result<(), StoreError>> + Send
+    where
+        Self: Send,
+    {
+        std::future::ready(self.refresh())
+    }
+
+    /// Remove all events from the storage
+    ///
+    /// This is meant for resetting a storage between tests, the default does nothing.
+    fn clear(&mut self) {}
+
+    /// Identifies the storage and the events in it together with the length of [EventStorage::all]
+    ///
+    /// Two storages have different generations and a storage gets a new one
+    /// whenever events are removed, so a cache keyed on the generation and the
+    /// length, like [CachedProjection](crate::events::projections::CachedProjection),
+    /// notices both. Storages that can remove events must override this, the
+    /// default is always 0.
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    /// Write all events as JSON Lines, one event per line
+    ///
+    /// Read them back with [read_jsonl], for example to move the events to
+    /// another kind of storage.
+    #[cfg(feature = "serde")]
+    fn write_jsonl<W: Write>(&self, mut writer: W) -> io::Result<()>
+    where
+        T: serde::Serialize,
+    {
+        for event in self.all() {
+            serde_json::to_writer(&mut writer, event)?;
+            writer.write_all(b"\n")?;
+        }
+
+        writer.flush()
+    }
+}
+
+/// A generation no other storage has been given, see [EventStorage::generation]
+pub(crate) fn next_generation() -> u64 {
+    static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Read events from JSON Lines written by [EventStorage::write_jsonl]
+///
+/// Empty lines are skipped.
+#[cfg(feature = "serde")]
+pub fn read_jsonl<T, R>(reader: R) -> Result<Vec<T>, ImportError>
+where
+    T: serde::de::DeserializeOwned,
+    R: Read,
+{
+    let mut events = Vec::new();
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = serde_json::from_str(&line).map_err(|source| ImportError::Parse {
+            line: index + 1,
+            source,
+        })?;
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, proptest};
+
+    use crate::{strategies, Event};
+
+    proptest! {
+        #[test]
+        fn write_then_read_jsonl_should_round_trip(events in vec(strategies::event(), 0..20)) {
+            let mut store = InMemoryStore::new();
+            store.append_many(events);
+
+            let mut buffer = Vec::new();
+            store.write_jsonl(&mut buffer).unwrap();
+            let actual = read_jsonl::<Event, _>(buffer.as_slice()).unwrap();
+
+            assert_eq!(actual, store.all());
+        }
+    }
 }
@@ -0,0 +1,127 @@
+/// A position where two event histories don't agree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Divergence<'a, T> {
+    /// The second history has an event the first history doesn't have
+    Extra { position: usize, event: &'a T },
+    /// The second history is missing an event from the first history
+    Missing { position: usize, event: &'a T },
+    /// Both histories have an event at this position but they are different
+    Changed {
+        position: usize,
+        left: &'a T,
+        right: &'a T,
+    },
+}
+
+impl<T> Divergence<'_, T> {
+    pub fn position(&self) -> usize {
+        match self {
+            Self::Extra { position, .. } => *position,
+            Self::Missing { position, .. } => *position,
+            Self::Changed { position, .. } => *position,
+        }
+    }
+}
+
+/// Compare two event histories position by position
+///
+/// Returns every position where the histories differ, in order, so the first
+/// element is where they start to diverge. Identical histories give an empty list.
+pub fn diff<'a, T: PartialEq>(a: &'a [T], b: &'a [T]) -> Vec<Divergence<'a, T>> {
+    let changed = a
+        .iter()
+        .zip(b)
+        .enumerate()
+        .filter(|(_, (left, right))| left != right)
+        .map(|(position, (left, right))| Divergence::Changed {
+            position,
+            left,
+            right,
+        });
+
+    let common = a.len().min(b.len());
+    let missing = a
+        .iter()
+        .enumerate()
+        .skip(common)
+        .map(|(position, event)| Divergence::Missing { position, event });
+    let extra = b
+        .iter()
+        .enumerate()
+        .skip(common)
+        .map(|(position, event)| Divergence::Extra { position, event });
+
+    changed.chain(missing).chain(extra).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{write::ledger::LedgerId, Event};
+
+    fn ledger(id: &str) -> Event {
+        Event::LedgerCreated {
+            id: LedgerId::new(id).unwrap(),
+        }
+    }
+
+    #[test]
+    fn diff_identical_histories_should_be_empty() {
+        let a = vec![ledger("2014"), ledger("2015")];
+        let b = a.clone();
+
+        assert_eq!(diff(&a, &b), vec![]);
+    }
+
+    #[test]
+    fn diff_prefix_should_report_extra_events() {
+        let a = vec![ledger("2014")];
+        let b = vec![ledger("2014"), ledger("2015"), ledger("2016")];
+
+        assert_eq!(
+            diff(&a, &b),
+            vec![
+                Divergence::Extra {
+                    position: 1,
+                    event: &b[1]
+                },
+                Divergence::Extra {
+                    position: 2,
+                    event: &b[2]
+                },
+            ]
+        );
+        assert_eq!(
+            diff(&b, &a),
+            vec![
+                Divergence::Missing {
+                    position: 1,
+                    event: &b[1]
+                },
+                Divergence::Missing {
+                    position: 2,
+                    event: &b[2]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_mid_stream_should_report_changed_event() {
+        let a = vec![ledger("2014"), ledger("2015"), ledger("2016")];
+        let b = vec![ledger("2014"), ledger("2020"), ledger("2016")];
+
+        let actual = diff(&a, &b);
+
+        assert_eq!(
+            actual,
+            vec![Divergence::Changed {
+                position: 1,
+                left: &a[1],
+                right: &b[1]
+            }]
+        );
+        assert_eq!(actual[0].position(), 1);
+    }
+}
@@ -1,31 +1,141 @@
-use std::{ops::Deref, sync::Arc};
+#[cfg(feature = "serde")]
+use std::io::{self, BufRead, Write};
+use std::ops::Deref;
 
-use crate::{events::{EventPointer, EventPointerType}, Event};
+#[cfg(feature = "serde")]
+use crate::error::ImportError;
 
-use super::EventStorage;
+use crate::{events::EventPointerType, Event};
+
+use super::{next_generation, EventStorage};
 
 pub struct InMemoryStore<T> {
     data: Vec<T>,
+    generation: u64,
 }
 
 impl<T> InMemoryStore<T> {
     pub fn new() -> InMemoryStore<T> {
-        Self { data: Vec::new() }
+        Self::from_vec(Vec::new())
+    }
+
+    fn from_vec(data: Vec<T>) -> Self {
+        Self {
+            data,
+            generation: next_generation(),
+        }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.data.iter()
     }
+
+    /// Iterate over the events newest first
+    pub fn iter_rev(&self) -> impl Iterator<Item = &T> {
+        self.data.iter().rev()
+    }
+
+    /// The last `n` events in the order they were appended
+    pub fn latest(&self, n: usize) -> &[T] {
+        &self.data[self.data.len().saturating_sub(n)..]
+    }
+
+    /// Iterate lazily over the events matching the predicate
+    pub fn filtered_iter<P>(&self, predicate: P) -> impl Iterator<Item = &T>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.data.iter().filter(move |x| predicate(x))
+    }
+
+    /// Iterate lazily over the events projected with `f`
+    pub fn map_iter<'a, U, F>(&'a self, f: F) -> impl Iterator<Item = U> + 'a
+    where
+        F: Fn(&T) -> U + 'a,
+    {
+        self.data.iter().map(f)
+    }
+
+    /// Remove all events from the store returning them in the order they were appended
+    pub fn drain(&mut self) -> Vec<T> {
+        self.generation = next_generation();
+        std::mem::take(&mut self.data)
+    }
+
+    /// Remove at most `n` of the oldest events from the store and return them
+    pub fn take(&mut self, n: usize) -> Vec<T> {
+        let n = n.min(self.data.len());
+        self.generation = next_generation();
+        self.data.drain(..n).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> InMemoryStore<T> {
+    /// Write all events as JSON Lines, one event per line
+    pub fn export_jsonl<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.write_jsonl(writer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned> InMemoryStore<T> {
+    /// Read a store from JSON Lines written by [InMemoryStore::export_jsonl]
+    ///
+    /// Empty lines are skipped.
+    pub fn import_jsonl<R: BufRead>(reader: R) -> Result<Self, ImportError> {
+        super::read_jsonl(reader).map(Self::from_vec)
+    }
+}
+
+impl<T: PartialEq> InMemoryStore<T> {
+    /// Append an event unless it is an exact duplicate of the last appended event
+    ///
+    /// Only the last event is compared since two identical events further apart,
+    /// like the same transaction on two occasions, can be legitimate.
+    /// Returns true if the event was appended.
+    pub fn append_unique(&mut self, event: T) -> bool {
+        self.append_unique_by(event, T::eq)
+    }
+}
+
+impl<T> InMemoryStore<T> {
+    /// Append an event unless `same` considers it a duplicate of the last appended event
+    ///
+    /// See [InMemoryStore::append_unique], use [Event::equivalent] to ignore the
+    /// order of the postings of a transaction.
+    pub fn append_unique_by<F>(&mut self, event: T, same: F) -> bool
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        match self.data.last() {
+            Some(last) if same(last, &event) => false,
+            _ => {
+                self.data.push(event);
+                true
+            }
+        }
+    }
 }
 
 impl<T> EventStorage<T> for InMemoryStore<T> {
-    fn append(&mut self, event: T) {
-        self.data.push(event)
+    fn append(&mut self, event: T) -> usize {
+        self.data.push(event);
+        self.data.len() - 1
     }
 
     fn all(&self) -> &[T] {
         &self.data
     }
+
+    fn clear(&mut self) {
+        self.generation = next_generation();
+        self.data.clear()
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
 }
 
 impl<T> IntoIterator for InMemoryStore<T> {
@@ -54,13 +164,15 @@ impl<T> Default for InMemoryStore<T> {
 
 impl Extend<EventPointerType> for InMemoryStore<Event> {
     fn extend<T: IntoIterator<Item = EventPointerType>>(&mut self, iter: T) {
-        self.data.extend(iter.into_iter().map(|x| x.deref().clone()))
+        self.data
+            .extend(iter.into_iter().map(|x| x.deref().clone()))
     }
 }
 
 impl<'a> Extend<&'a EventPointerType> for InMemoryStore<Event> {
     fn extend<T: IntoIterator<Item = &'a EventPointerType>>(&mut self, iter: T) {
-        self.data.extend(iter.into_iter().map(Deref::deref).cloned())
+        self.data
+            .extend(iter.into_iter().map(Deref::deref).cloned())
     }
 }
 
@@ -69,3 +181,208 @@ impl<T> Extend<T> for InMemoryStore<T> {
         self.data.extend(iter);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::prelude::*;
+
+    use crate::write::ledger::LedgerId;
+    #[cfg(feature = "serde")]
+    use test_case::test_case;
+
+    fn store(events: &[u32]) -> InMemoryStore<u32> {
+        let mut store = InMemoryStore::new();
+        store.extend(events.iter().copied());
+        store
+    }
+
+    #[test]
+    fn drain_should_leave_store_empty() {
+        let mut store = store(&[1, 2, 3]);
+
+        let drained = store.drain();
+
+        assert_eq!(drained, vec![1, 2, 3]);
+        assert!(store.all().is_empty());
+    }
+
+    #[test]
+    fn take_should_leave_remainder() {
+        let mut store = store(&[1, 2, 3]);
+
+        let taken = store.take(2);
+
+        assert_eq!(taken, vec![1, 2]);
+        assert_eq!(store.all(), &[3]);
+    }
+
+    #[test]
+    fn take_more_than_available_should_drain_everything() {
+        let mut store = store(&[1, 2]);
+
+        let taken = store.take(5);
+
+        assert_eq!(taken, vec![1, 2]);
+        assert!(store.all().is_empty());
+    }
+
+    #[test]
+    fn append_should_return_incrementing_positions() {
+        let mut store = store(&[1, 2]);
+
+        assert_eq!(store.append(3), 2);
+        assert_eq!(store.append(4), 3);
+        assert_eq!(store.append_many([5, 6, 7]), 4..7);
+        assert_eq!(store.append_many([]), 7..7);
+        assert_eq!(store.all()[4..7], [5, 6, 7]);
+    }
+
+    #[test]
+    fn append_unique_should_skip_duplicate_event() {
+        let mut store = InMemoryStore::new();
+        let event = Event::LedgerCreated {
+            id: LedgerId::new("2014").unwrap(),
+        };
+
+        assert!(store.append_unique(event.clone()));
+        assert!(!store.append_unique(event.clone()));
+        assert_eq!(store.all(), &[event]);
+    }
+
+    #[test]
+    fn append_unique_by_equivalent_should_skip_reordered_transaction() {
+        let transaction = |postings: [crate::Posting; 2]| Event::Transaction {
+            ledger: LedgerId::new("2014").unwrap(),
+            description: String::from("Groceries"),
+            date: Utc.ymd(2014, 4, 1),
+            transactions: postings.to_vec(),
+            metadata: Default::default(),
+        };
+        let debit = crate::Posting::debit(crate::Number::new(501).unwrap(), 150).unwrap();
+        let credit = crate::Posting::credit(crate::Number::new(101).unwrap(), 150).unwrap();
+        let mut store = InMemoryStore::new();
+
+        assert!(store.append_unique_by(transaction([debit, credit]), Event::equivalent));
+        assert!(!store.append_unique_by(transaction([credit, debit]), Event::equivalent));
+        assert_eq!(store.all().len(), 1);
+    }
+
+    #[test]
+    fn append_unique_should_append_non_consecutive_duplicate() {
+        let mut store = store(&[1, 2]);
+
+        assert!(store.append_unique(1));
+        assert_eq!(store.all(), &[1, 2, 1]);
+    }
+
+    #[test]
+    fn filtered_iter_should_only_yield_matching_events() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let transaction = Event::Transaction {
+            ledger: ledger.clone(),
+            description: String::from("Groceries"),
+            date: Utc.ymd(2014, 4, 1),
+            transactions: Vec::new(),
+            metadata: Default::default(),
+        };
+
+        let mut store = InMemoryStore::new();
+        store.append(Event::LedgerCreated { id: ledger.clone() });
+        store.append(transaction.clone());
+        store.append(Event::PeriodSealed {
+            ledger,
+            up_to: Utc.ymd(2014, 4, 30),
+        });
+
+        let actual = store
+            .filtered_iter(|e| matches!(e, Event::Transaction { .. }))
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, vec![&transaction]);
+    }
+
+    #[test]
+    fn map_iter_should_project_events() {
+        let store = store(&[1, 2, 3]);
+
+        let actual = store.map_iter(|x| x * 10).collect::<Vec<_>>();
+
+        assert_eq!(actual, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn iter_rev_should_yield_newest_first() {
+        let store = store(&[1, 2, 3]);
+
+        assert_eq!(store.iter_rev().copied().collect::<Vec<_>>(), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn latest_should_be_in_chronological_order() {
+        let store = store(&[1, 2, 3, 4, 5]);
+
+        assert_eq!(store.latest(2), &[4, 5]);
+        assert_eq!(store.latest(10), &[1, 2, 3, 4, 5]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_then_import_jsonl_should_round_trip() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let mut store = InMemoryStore::new();
+        store.append(Event::LedgerCreated { id: ledger.clone() });
+        store.append(Event::Transaction {
+            ledger,
+            description: String::from("Groceries"),
+            date: Utc.ymd(2014, 4, 1),
+            transactions: vec![
+                crate::Posting::debit(crate::Number::new(501).unwrap(), 150).unwrap(),
+                crate::Posting::credit(crate::Number::new(101).unwrap(), 150)
+                    .unwrap()
+                    .with_currency(crate::Currency::new("SEK").unwrap()),
+            ],
+            metadata: [(String::from("project"), String::from("home"))].into(),
+        });
+
+        let mut buffer = Vec::new();
+        store.export_jsonl(&mut buffer).unwrap();
+        assert_eq!(buffer.iter().filter(|x| **x == b'\n').count(), 2);
+
+        let imported = InMemoryStore::<Event>::import_jsonl(buffer.as_slice()).unwrap();
+
+        assert_eq!(imported.all(), store.all());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn import_jsonl_should_report_line_of_invalid_event() {
+        let input = "{\"LedgerCreated\":{\"id\":\"2014\"}}\nnot json\n";
+
+        let actual = InMemoryStore::<Event>::import_jsonl(input.as_bytes());
+
+        assert!(matches!(actual, Err(ImportError::Parse { line: 2, .. })));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test_case(r#"{"LedgerCreated":{"id":"2014"}}"# => true ; "valid ledger id")]
+    #[test_case(r#"{"LedgerCreated":{"id":"-bad id!"}}"# => false ; "invalid ledger id")]
+    #[test_case(r#"{"AccountOpened":{"ledger":"2014","id":101,"name":"Bank","category":"Asset"}}"# => true ; "valid name")]
+    #[test_case(r#"{"AccountOpened":{"ledger":"2014","id":101,"name":"   ","category":"Asset"}}"# => false ; "blank name")]
+    #[test_case(r#"{"Transaction":{"ledger":"2014","description":"","date":"2014-04-01","transactions":[{"account":101,"amount":{"Debit":5},"currency":null}],"metadata":{}}}"# => true ; "nonzero amount")]
+    #[test_case(r#"{"Transaction":{"ledger":"2014","description":"","date":"2014-04-01","transactions":[{"account":101,"amount":{"Debit":0},"currency":null}],"metadata":{}}}"# => false ; "zero amount")]
+    fn import_jsonl_should_validate_values(line: &str) -> bool {
+        InMemoryStore::<Event>::import_jsonl(line.as_bytes()).is_ok()
+    }
+
+    #[test]
+    fn clear_should_empty_the_store() {
+        let mut store = store(&[1, 2, 3]);
+        store.append(4);
+
+        store.clear();
+
+        assert!(store.all().is_empty());
+    }
+}
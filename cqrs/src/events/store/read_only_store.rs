@@ -0,0 +1,85 @@
+use super::EventStorage;
+
+/// A read only view of an event store
+///
+/// This is handed to read models to make sure they can't append any events.
+///
+/// ```compile_fail
+/// use cqrs::events::store::{EventStorage, InMemoryStore, ReadOnlyStore};
+///
+/// let store = InMemoryStore::<u32>::new();
+/// let mut view = ReadOnlyStore::new(&store);
+/// view.append(1);
+/// ```
+#[derive(Debug)]
+pub struct ReadOnlyStore<'a, S> {
+    store: &'a S,
+}
+
+impl<'a, S> ReadOnlyStore<'a, S> {
+    pub fn new(store: &'a S) -> Self {
+        Self { store }
+    }
+
+    pub fn all<T>(&self) -> &'a [T]
+    where
+        S: EventStorage<T>,
+    {
+        self.store.all()
+    }
+
+    pub fn len<T>(&self) -> usize
+    where
+        S: EventStorage<T>,
+    {
+        self.all::<T>().len()
+    }
+
+    pub fn is_empty<T>(&self) -> bool
+    where
+        S: EventStorage<T>,
+    {
+        self.all::<T>().is_empty()
+    }
+
+    pub fn iter<T: 'a>(&self) -> impl Iterator<Item = &'a T>
+    where
+        S: EventStorage<T>,
+    {
+        self.all().iter()
+    }
+}
+
+impl<S> Clone for ReadOnlyStore<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for ReadOnlyStore<'_, S> {}
+
+impl<'a, S> From<&'a S> for ReadOnlyStore<'a, S> {
+    fn from(store: &'a S) -> Self {
+        Self::new(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::events::store::InMemoryStore;
+
+    #[test]
+    fn reads_should_see_the_underlying_store() {
+        let mut store = InMemoryStore::new();
+        store.extend([1, 2, 3]);
+
+        let view = ReadOnlyStore::new(&store);
+
+        assert_eq!(view.len::<u32>(), 3);
+        assert!(!view.is_empty::<u32>());
+        assert_eq!(view.all::<u32>(), &[1, 2, 3]);
+        assert_eq!(view.iter::<u32>().sum::<u32>(), 6);
+    }
+}
@@ -0,0 +1,292 @@
+use std::{future::Future, ops::Range};
+
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::StoreError;
+
+use super::{next_generation, EventStorage};
+
+enum Request<T> {
+    /// Append the events only if the store still holds `since` events
+    Append {
+        events: Vec<T>,
+        since: usize,
+        reply: oneshot::Sender<Result<Range<usize>, StoreError>>,
+    },
+    Fetch {
+        since: usize,
+        reply: oneshot::Sender<Vec<T>>,
+    },
+}
+
+/// A handle to a store owned by a background task
+///
+/// Several proxies, for example one per `CommandHandler`, can share one store
+/// without locking it. Every request is sent to the task owning the store which
+/// handles them one at a time in the order they arrive.
+///
+/// [EventStorage::append_all] is checked against the history of the proxy: if
+/// another proxy has appended events since the last refresh the append is
+/// rejected with [StoreError::Conflict] and nothing is stored. This keeps a
+/// command that was validated against an old history from being committed.
+/// The other appends catch up on the history and retry until they succeed.
+///
+/// Inside an async runtime use [EventStorage::refresh_async] and
+/// [EventStorage::append_all_async], the other methods block the thread waiting
+/// for the owning task and must not be called from the runtime. A stopped owner
+/// is reported as [StoreError::Unavailable] by the methods that return a
+/// result. [EventStorage::append], [EventStorage::append_many] and [Extend]
+/// can't report it and panic.
+/// [EventStorage::clear] is not forwarded since other proxies would keep their
+/// cached history.
+#[derive(Debug)]
+pub struct StoreProxy<T> {
+    sender: mpsc::UnboundedSender<Request<T>>,
+    history: Vec<T>,
+    /// Shared by the proxies of one store since their histories only grow
+    generation: u64,
+}
+
+impl<T> StoreProxy<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Move `store` to a new task and return the first proxy to it
+    ///
+    /// This must be called from within a tokio runtime. The task stops when the
+    /// last proxy is dropped.
+    pub fn spawn<S>(mut store: S) -> Self
+    where
+        S: EventStorage<T> + Send + 'static,
+    {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Request<T>>();
+        let history = store.all().to_vec();
+
+        tokio::spawn(async move {
+            while let Some(request) = receiver.recv().await {
+                match request {
+                    Request::Append {
+                        events,
+                        since,
+                        reply,
+                    } => {
+                        let actual = store.all().len();
+                        let result = if actual == since {
+                            store
+                                .append_all(&events)
+                                .map(|()| actual..store.all().len())
+                        } else {
+                            Err(StoreError::Conflict {
+                                expected: since,
+                                actual,
+                            })
+                        };
+                        let _ = reply.send(result);
+                    }
+                    Request::Fetch { since, reply } => {
+                        let _ = reply.send(store.all()[since..].to_vec());
+                    }
+                }
+            }
+        });
+
+        Self {
+            sender,
+            history,
+            generation: next_generation(),
+        }
+    }
+
+    fn request<R>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<R>) -> Request<T>,
+    ) -> oneshot::Receiver<R> {
+        let (reply, response) = oneshot::channel();
+        // A failed send drops the reply sender, which the receiver reports
+        let _ = self.sender.send(request(reply));
+        response
+    }
+
+    /// Append the events after those appended through other proxies
+    fn append_latest(&mut self, events: &[T]) -> Result<Range<usize>, StoreError> {
+        loop {
+            self.refresh()?;
+            let start = self.history.len();
+            match self.append_all(events) {
+                Ok(()) => return Ok(start..self.history.len()),
+                Err(StoreError::Conflict { .. }) => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn push(&mut self, events: &[T]) -> Range<usize> {
+        self.append_latest(events)
+            .expect("the store could not be appended to, use append_all to handle it")
+    }
+}
+
+impl<T: Clone> Clone for StoreProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            history: self.history.clone(),
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> EventStorage<T> for StoreProxy<T>
+where
+    T: Clone + Send + 'static,
+{
+    /// Returns the position of the event in the shared store
+    fn append(&mut self, event: T) -> usize {
+        self.push(&[event]).start
+    }
+
+    /// The history as of the last append or refresh of this proxy
+    fn all(&self) -> &[T] {
+        &self.history
+    }
+
+    fn append_many<I>(&mut self, events: I) -> Range<usize>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.push(&events.into_iter().collect::<Vec<_>>())
+    }
+
+    fn append_all(&mut self, events: &[T]) -> Result<(), StoreError> {
+        let since = self.history.len();
+        self.request(|reply| Request::Append {
+            events: events.to_vec(),
+            since,
+            reply,
+        })
+        .blocking_recv()
+        .map_err(|_| StoreError::Unavailable)??;
+        self.history.extend_from_slice(events);
+        Ok(())
+    }
+
+    fn append_all_async(
+        &mut self,
+        events: &[T],
+    ) -> impl Future<Output = Result<(), StoreError>> + Send {
+        let events = events.to_vec();
+        async move {
+            let since = self.history.len();
+            self.request(|reply| Request::Append {
+                events: events.clone(),
+                since,
+                reply,
+            })
+            .await
+            .map_err(|_| StoreError::Unavailable)??;
+            self.history.extend(events);
+            Ok(())
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Fetch the events appended through other proxies
+    fn refresh(&mut self) -> Result<(), StoreError> {
+        let since = self.history.len();
+        let missing = self
+            .request(|reply| Request::Fetch { since, reply })
+            .blocking_recv()
+            .map_err(|_| StoreError::Unavailable)?;
+        self.history.extend(missing);
+        Ok(())
+    }
+
+    async fn refresh_async(&mut self) -> Result<(), StoreError> {
+        let since = self.history.len();
+        let missing = self
+            .request(|reply| Request::Fetch { since, reply })
+            .await
+            .map_err(|_| StoreError::Unavailable)?;
+        self.history.extend(missing);
+        Ok(())
+    }
+}
+
+impl<T> Extend<T> for StoreProxy<T>
+where
+    T: Clone + Send + 'static,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.push(&iter.into_iter().collect::<Vec<_>>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::events::store::InMemoryStore;
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn proxies_should_share_one_history() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let mut store = InMemoryStore::new();
+        store.append(1);
+        let mut first = StoreProxy::spawn(store);
+        let mut second = first.clone();
+
+        assert_eq!(first.append(2), 1);
+        assert_eq!(second.append(3), 2);
+        assert_eq!(first.append_many([4, 5]), 3..5);
+
+        assert_eq!(first.all(), &[1, 2, 3, 4, 5]);
+        assert_eq!(second.all(), &[1, 2, 3]);
+
+        second.refresh().unwrap();
+        assert_eq!(second.all(), first.all());
+    }
+
+    #[tokio::test]
+    async fn append_all_should_reject_a_stale_proxy() {
+        let mut first = StoreProxy::spawn(InMemoryStore::new());
+        let mut second = first.clone();
+
+        first.append_all_async(&[1, 2]).await.unwrap();
+        assert_eq!(
+            second.append_all_async(&[3]).await,
+            Err(StoreError::Conflict {
+                expected: 0,
+                actual: 2
+            })
+        );
+
+        second.refresh_async().await.unwrap();
+        second.append_all_async(&[3]).await.unwrap();
+        first.refresh_async().await.unwrap();
+        assert_eq!(first.all(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn stopped_owner_should_be_reported() {
+        let runtime = runtime();
+        let mut proxy = {
+            let _guard = runtime.enter();
+            StoreProxy::spawn(InMemoryStore::<u32>::new())
+        };
+        drop(runtime);
+
+        assert_eq!(proxy.refresh(), Err(StoreError::Unavailable));
+        assert_eq!(proxy.append_all(&[1]), Err(StoreError::Unavailable));
+    }
+}
@@ -0,0 +1,163 @@
+use super::EventStorage;
+use crate::error::StoreError;
+
+/// A store that writes every event to two backends
+///
+/// Reads are always served from the primary store `A`. Writes go to the primary
+/// store first and then to the secondary store `B`. Only a failure of the primary
+/// store is reported by [EventStorage::append_all], the primary store is
+/// considered the source of truth. Failures of the secondary store are counted
+/// by [TeeStore::secondary_failures] so a drift between the stores can be noticed.
+#[derive(Debug, Default)]
+pub struct TeeStore<A, B> {
+    primary: A,
+    secondary: B,
+    secondary_failures: usize,
+}
+
+impl<A, B> TeeStore<A, B> {
+    pub fn new(primary: A, secondary: B) -> Self {
+        Self {
+            primary,
+            secondary,
+            secondary_failures: 0,
+        }
+    }
+
+    /// Number of appends and refreshes that failed on the secondary store
+    ///
+    /// The secondary store is missing events or out of date when this isn't zero.
+    pub fn secondary_failures(&self) -> usize {
+        self.secondary_failures
+    }
+
+    pub fn primary(&self) -> &A {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &B {
+        &self.secondary
+    }
+
+    /// Split the tee into its primary and secondary store
+    pub fn into_inner(self) -> (A, B) {
+        (self.primary, self.secondary)
+    }
+}
+
+impl<T, A, B> EventStorage<T> for TeeStore<A, B>
+where
+    T: Clone,
+    A: EventStorage<T>,
+    B: EventStorage<T>,
+{
+    /// Returns the position given by the primary store
+    fn append(&mut self, event: T) -> usize {
+        let position = self.primary.append(event.clone());
+        self.secondary.append(event);
+        position
+    }
+
+    fn append_all(&mut self, events: &[T]) -> Result<(), StoreError> {
+        self.primary.append_all(events)?;
+        if self.secondary.append_all(events).is_err() {
+            self.secondary_failures += 1;
+        }
+        Ok(())
+    }
+
+    fn refresh(&mut self) -> Result<(), StoreError> {
+        self.primary.refresh()?;
+        if self.secondary.refresh().is_err() {
+            self.secondary_failures += 1;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.primary.clear();
+        self.secondary.clear();
+    }
+
+    fn all(&self) -> &[T] {
+        self.primary.all()
+    }
+
+    fn generation(&self) -> u64 {
+        self.primary.generation()
+    }
+}
+
+impl<T, A, B> Extend<T> for TeeStore<A, B>
+where
+    T: Clone,
+    A: Extend<T>,
+    B: Extend<T>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let events = iter.into_iter().collect::<Vec<_>>();
+        self.primary.extend(events.iter().cloned());
+        self.secondary.extend(events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        events::{store::InMemoryStore, EventPointer},
+        write::ledger::LedgerId,
+        Event,
+    };
+
+    fn ledger(id: &str) -> Event {
+        Event::LedgerCreated {
+            id: LedgerId::new(id).unwrap(),
+        }
+    }
+
+    /// A store that is always unavailable
+    struct FailingStore;
+
+    impl EventStorage<Event> for FailingStore {
+        fn append(&mut self, _event: Event) -> usize {
+            0
+        }
+
+        fn all(&self) -> &[Event] {
+            &[]
+        }
+
+        fn append_all(&mut self, _events: &[Event]) -> Result<(), StoreError> {
+            Err(StoreError::Unavailable)
+        }
+
+        fn refresh(&mut self) -> Result<(), StoreError> {
+            Err(StoreError::Unavailable)
+        }
+    }
+
+    #[test]
+    fn failing_secondary_should_be_counted_but_not_reported() {
+        let mut store = TeeStore::new(InMemoryStore::new(), FailingStore);
+
+        assert_eq!(store.append_all(&[ledger("2014")]), Ok(()));
+        assert_eq!(store.refresh(), Ok(()));
+
+        assert_eq!(store.all(), &[ledger("2014")]);
+        assert_eq!(store.secondary_failures(), 2);
+    }
+
+    #[test]
+    fn both_stores_should_receive_the_same_events() {
+        let mut store = TeeStore::new(InMemoryStore::new(), InMemoryStore::new());
+
+        store.append(ledger("2014"));
+        store.extend(vec![Event::new(ledger("2015")), Event::new(ledger("2016"))]);
+
+        let expected = vec![ledger("2014"), ledger("2015"), ledger("2016")];
+        assert_eq!(store.all(), expected.as_slice());
+        assert_eq!(store.primary().all(), store.secondary().all());
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{error::HistoryError, write::ledger::LedgerId, Event, Number, Postings};
+
+/// Check the global invariants of an event history
+///
+/// A ledger must only be created once and before any event referencing it,
+/// accounts must be opened before they are used or closed and every
+/// transaction must balance. All violations are returned, not just the first.
+pub fn validate_history(events: &[Event]) -> Result<(), Vec<HistoryError>> {
+    let mut ledgers = HashSet::<&LedgerId>::new();
+    let mut accounts = HashMap::<&LedgerId, HashSet<Number>>::new();
+    let mut errors = Vec::new();
+
+    for (position, event) in events.iter().enumerate() {
+        match event {
+            Event::LedgerCreated { id } => {
+                if !ledgers.insert(id) {
+                    errors.push(HistoryError::DuplicateLedger {
+                        position,
+                        ledger: id.clone(),
+                    });
+                }
+                continue;
+            }
+            // A rejected command may refer to a ledger that was never created
+            Event::CommandRejected { .. } => continue,
+            Event::AccountOpened { ledger, .. }
+            | Event::AccountClosed { ledger, .. }
+            | Event::AccountRecategorized { ledger, .. }
+            | Event::Transaction { ledger, .. }
+            | Event::PeriodSealed { ledger, .. } => {
+                if !ledgers.contains(ledger) {
+                    errors.push(HistoryError::LedgerNotCreated {
+                        position,
+                        ledger: ledger.clone(),
+                    });
+                }
+            }
+        }
+
+        match event {
+            Event::AccountOpened { ledger, id, .. } => {
+                accounts.entry(ledger).or_default().insert(*id);
+            }
+            Event::AccountClosed { ledger, account } => {
+                let removed = accounts
+                    .get_mut(ledger)
+                    .map(|chart| chart.remove(account))
+                    .unwrap_or_default();
+
+                if !removed {
+                    errors.push(HistoryError::AccountNotOpen {
+                        position,
+                        account: *account,
+                    });
+                }
+            }
+            Event::AccountRecategorized {
+                ledger, account, ..
+            } => {
+                let is_open = accounts
+                    .get(ledger)
+                    .map(|chart| chart.contains(account))
+                    .unwrap_or_default();
+
+                if !is_open {
+                    errors.push(HistoryError::AccountNotOpen {
+                        position,
+                        account: *account,
+                    });
+                }
+            }
+            Event::Transaction {
+                ledger,
+                transactions,
+                ..
+            } => {
+                let chart = accounts.get(ledger);
+                errors.extend(
+                    transactions
+                        .iter()
+                        .filter(|posting| {
+                            !chart
+                                .map(|chart| chart.contains(&posting.account()))
+                                .unwrap_or_default()
+                        })
+                        .map(|posting| HistoryError::AccountNotOpen {
+                            position,
+                            account: posting.account(),
+                        }),
+                );
+
+                if !Postings::from(transactions.clone()).is_balanced() {
+                    errors.push(HistoryError::ImbalancedTransaction { position });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::prelude::*;
+
+    use crate::{Category, Posting};
+
+    fn ledger() -> LedgerId {
+        LedgerId::new("2014").unwrap()
+    }
+
+    fn open(number: u32) -> Event {
+        Event::account_opened(ledger(), number, "Account", Category::Asset).unwrap()
+    }
+
+    fn transaction(debit: u32, credit: u32, amount: u32) -> Event {
+        Event::Transaction {
+            ledger: ledger(),
+            description: String::from("Transfer"),
+            date: Utc.ymd(2014, 4, 1),
+            transactions: vec![
+                Posting::debit(Number::new(debit).unwrap(), amount).unwrap(),
+                Posting::credit(Number::new(credit).unwrap(), amount).unwrap(),
+            ],
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn clean_history_is_valid() {
+        let history = vec![
+            Event::LedgerCreated { id: ledger() },
+            open(101),
+            open(102),
+            transaction(101, 102, 50),
+            Event::AccountClosed {
+                ledger: ledger(),
+                account: Number::new(102).unwrap(),
+            },
+        ];
+
+        assert_eq!(validate_history(&history), Ok(()));
+    }
+
+    #[test]
+    fn duplicate_ledger_is_invalid() {
+        let history = vec![
+            Event::LedgerCreated { id: ledger() },
+            Event::LedgerCreated { id: ledger() },
+        ];
+
+        assert_eq!(
+            validate_history(&history),
+            Err(vec![HistoryError::DuplicateLedger {
+                position: 1,
+                ledger: ledger()
+            }])
+        );
+    }
+
+    #[test]
+    fn account_opened_before_ledger_is_invalid() {
+        let history = vec![open(101), Event::LedgerCreated { id: ledger() }];
+
+        assert_eq!(
+            validate_history(&history),
+            Err(vec![HistoryError::LedgerNotCreated {
+                position: 0,
+                ledger: ledger()
+            }])
+        );
+    }
+
+    #[test]
+    fn all_violations_are_reported() {
+        let mut imbalanced = transaction(101, 102, 50);
+        if let Event::Transaction { transactions, .. } = &mut imbalanced {
+            transactions.pop();
+        }
+
+        let history = vec![
+            Event::LedgerCreated { id: ledger() },
+            open(101),
+            transaction(101, 102, 50),
+            imbalanced,
+        ];
+
+        assert_eq!(
+            validate_history(&history),
+            Err(vec![
+                HistoryError::AccountNotOpen {
+                    position: 2,
+                    account: Number::new(102).unwrap()
+                },
+                HistoryError::ImbalancedTransaction { position: 3 },
+            ])
+        );
+    }
+}
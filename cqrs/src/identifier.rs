@@ -6,17 +6,42 @@ pub struct Identifier(String);
 /// Identifier is any alphanumeric character and \[_-\]
 impl Identifier {
     pub fn new<T: AsRef<str>>(identifier: T) -> Option<Self> {
+        Self::new_with(
+            |x| x.is_alphanumeric() || matches!(x, '_' | '-'),
+            identifier,
+        )
+    }
+
+    /// Create an identifier where every character must satisfy `predicate`
+    ///
+    /// This widens or narrows the set of characters allowed by [Identifier::new],
+    /// an empty identifier is never valid.
+    pub fn new_with<P, T>(predicate: P, identifier: T) -> Option<Self>
+    where
+        P: Fn(char) -> bool,
+        T: AsRef<str>,
+    {
         let identifier = identifier.as_ref();
         if identifier.is_empty() {
             return None;
         }
 
-        if identifier.chars().all(|x| x.is_alphanumeric() || matches!(x, '_' | '-')) {
+        if identifier.chars().all(predicate) {
             Some(Self(identifier.into()))
         } else {
             None
         }
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl FromStr for Identifier {
@@ -38,6 +63,37 @@ impl fmt::Display for ParseError {
 
 impl Error for ParseError {}
 
+/// Sequence number of a journal, kept apart from account numbers and amounts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct JournalId(u32);
+
+impl JournalId {
+    /// The id following this one, or None if the ids have run out
+    pub fn next(self) -> Option<Self> {
+        self.0.checked_add(1).map(Self)
+    }
+}
+
+impl fmt::Display for JournalId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<u32> for JournalId {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<JournalId> for u32 {
+    fn from(value: JournalId) -> Self {
+        value.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +126,29 @@ mod tests {
 
         left == right
     }
+
+    #[test_case(0 => Some(1))]
+    #[test_case(41 => Some(42))]
+    #[test_case(u32::MAX => None)]
+    fn journal_id_next(id: u32) -> Option<u32> {
+        JournalId::from(id).next().map(u32::from)
+    }
+
+    #[test]
+    fn journal_id_should_display_as_number() {
+        assert_eq!(JournalId::from(7).to_string(), "7");
+    }
+
+    #[test_case("2014-01" => true ; "hyphen")]
+    #[test_case("2014:01" => false ; "colon")]
+    fn new_default_characters(input: &str) -> bool {
+        Identifier::new(input).is_some()
+    }
+
+    #[test_case("2014:01" => Some(Identifier("2014:01".to_owned())) ; "widened")]
+    #[test_case("2014_01" => None ; "narrowed")]
+    #[test_case("" => None ; "empty")]
+    fn new_with_custom_predicate(input: &str) -> Option<Identifier> {
+        Identifier::new_with(|x| x.is_ascii_digit() || x == ':', input)
+    }
 }
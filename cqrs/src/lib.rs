@@ -1,16 +1,23 @@
+pub use currency::Currency;
 pub use events::Event;
+pub use identifier::JournalId;
 pub use personal_finance::{
     account::{Category, Name, Number},
     balance::Balance,
 };
 
+pub mod currency;
 pub mod error;
 pub mod events;
 pub mod identifier;
+pub mod posting;
+pub mod projections;
+#[cfg(feature = "serde")]
+mod serde_date;
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies;
 pub mod stream;
 pub mod write;
-pub mod projections;
 
+pub use posting::{Posting, Postings};
 pub use write::ledger::Ledger;
-
-pub type JournalId = u32;
@@ -0,0 +1,275 @@
+use std::{collections::BTreeSet, num::NonZeroU32};
+
+use personal_finance::{account::Number, balance::Balance};
+
+use crate::{error::TransactionError, Currency};
+
+/// A single line of a transaction debiting or crediting an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Posting {
+    account: Number,
+    amount: Balance,
+    #[cfg_attr(feature = "serde", serde(default))]
+    currency: Option<Currency>,
+}
+
+impl Posting {
+    pub fn new(account: Number, amount: Balance) -> Self {
+        Self {
+            account,
+            amount,
+            currency: None,
+        }
+    }
+
+    /// Set the currency of the posting
+    ///
+    /// A posting without a currency is in the ledger's base currency.
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// Create a posting that debits the account
+    pub fn debit<T: TryInto<NonZeroU32>>(account: Number, amount: T) -> Option<Self> {
+        Balance::debit(amount).map(|amount| Self::new(account, amount))
+    }
+
+    /// Create a posting that credits the account
+    pub fn credit<T: TryInto<NonZeroU32>>(account: Number, amount: T) -> Option<Self> {
+        Balance::credit(amount).map(|amount| Self::new(account, amount))
+    }
+
+    pub fn account(&self) -> Number {
+        self.account
+    }
+
+    pub fn amount(&self) -> &Balance {
+        &self.amount
+    }
+
+    pub fn currency(&self) -> Option<Currency> {
+        self.currency
+    }
+
+    pub fn is_debit(&self) -> bool {
+        matches!(self.amount, Balance::Debit(_))
+    }
+}
+
+impl From<(Number, Balance)> for Posting {
+    fn from(value: (Number, Balance)) -> Self {
+        let (account, amount) = value;
+        Self::new(account, amount)
+    }
+}
+
+impl From<Posting> for (Number, Balance) {
+    /// Convert the posting into a tuple, the currency is discarded
+    fn from(value: Posting) -> Self {
+        (value.account, value.amount)
+    }
+}
+
+/// A collection of postings that make up a transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Postings(Vec<Posting>);
+
+impl Postings {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, posting: Posting) {
+        self.0.push(posting);
+    }
+
+    /// The signed sum of all postings where debits are positive and credits are negative
+    pub fn balance(&self) -> i64 {
+        self.0
+            .iter()
+            .map(|posting| match posting.amount {
+                Balance::Debit(x) => i64::from(x.amount()),
+                Balance::Credit(x) => -i64::from(x.amount()),
+            })
+            .sum()
+    }
+
+    /// Returns true if the debits and credits sum to zero
+    pub fn is_balanced(&self) -> bool {
+        self.balance() == 0
+    }
+
+    /// Returns true if all postings share the same currency
+    pub fn is_single_currency(&self) -> bool {
+        self.0
+            .windows(2)
+            .all(|pair| pair[0].currency == pair[1].currency)
+    }
+
+    /// Accounts that are both debited and credited in these postings, in ascending order
+    ///
+    /// This can be intentional but is often a data entry error.
+    pub fn self_offsetting(&self) -> Vec<Number> {
+        let debited = self
+            .0
+            .iter()
+            .filter(|posting| posting.is_debit())
+            .map(|posting| posting.account)
+            .collect::<BTreeSet<_>>();
+
+        self.0
+            .iter()
+            .filter(|posting| !posting.is_debit())
+            .map(|posting| posting.account)
+            .filter(|account| debited.contains(account))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns the postings only if they are balanced
+    pub fn try_into_balanced(self) -> Result<Self, TransactionError> {
+        self.is_balanced()
+            .then_some(self)
+            .ok_or(TransactionError::ImbalancedTranasactions)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Posting> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[Posting] {
+        &self.0
+    }
+
+    /// Move the inner vector out of [Postings] thus consuming it
+    pub fn into_inner(self) -> Vec<Posting> {
+        self.0
+    }
+}
+
+impl From<Vec<Posting>> for Postings {
+    fn from(value: Vec<Posting>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Postings> for Vec<Posting> {
+    fn from(value: Postings) -> Self {
+        value.0
+    }
+}
+
+impl FromIterator<Posting> for Postings {
+    fn from_iter<T: IntoIterator<Item = Posting>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Postings {
+    type IntoIter = std::vec::IntoIter<Posting>;
+    type Item = Posting;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Postings {
+    type IntoIter = std::slice::Iter<'a, Posting>;
+    type Item = &'a Posting;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_offsetting_lists_accounts_on_both_sides_once() {
+        let postings = Postings::from(vec![
+            Posting::credit(Number::new(501).unwrap(), 10).unwrap(),
+            Posting::debit(Number::new(501).unwrap(), 20).unwrap(),
+            Posting::debit(Number::new(101).unwrap(), 5).unwrap(),
+            Posting::credit(Number::new(101).unwrap(), 5).unwrap(),
+            Posting::credit(Number::new(101).unwrap(), 10).unwrap(),
+            Posting::credit(Number::new(201).unwrap(), 10).unwrap(),
+        ]);
+
+        assert_eq!(
+            postings.self_offsetting(),
+            vec![Number::new(101).unwrap(), Number::new(501).unwrap()]
+        );
+    }
+
+    #[test]
+    fn debit_posting_is_debit() {
+        let posting = Posting::debit(Number::new(101).unwrap(), 50).unwrap();
+
+        assert!(posting.is_debit());
+        assert_eq!(posting.amount(), &Balance::debit(50).unwrap());
+    }
+
+    #[test]
+    fn credit_posting_is_not_debit() {
+        let posting = Posting::credit(Number::new(101).unwrap(), 50).unwrap();
+
+        assert!(!posting.is_debit());
+    }
+
+    #[test]
+    fn posting_from_tuple_round_trips() {
+        let tuple = (Number::new(101).unwrap(), Balance::credit(50).unwrap());
+
+        let posting = Posting::from(tuple);
+
+        assert_eq!(<(Number, Balance)>::from(posting), tuple);
+    }
+
+    fn postings(debit: u32, credit: u32) -> Postings {
+        vec![
+            Posting::debit(Number::new(501).unwrap(), debit).unwrap(),
+            Posting::credit(Number::new(101).unwrap(), credit).unwrap(),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn balanced_postings() {
+        let postings = postings(150, 150);
+
+        assert_eq!(postings.balance(), 0);
+        assert!(postings.is_balanced());
+        assert_eq!(postings.clone().try_into_balanced(), Ok(postings));
+    }
+
+    #[test]
+    fn unbalanced_postings() {
+        let postings = postings(150, 100);
+
+        assert_eq!(postings.balance(), 50);
+        assert!(!postings.is_balanced());
+        assert_eq!(
+            postings.try_into_balanced(),
+            Err(TransactionError::ImbalancedTranasactions)
+        );
+    }
+
+    #[test]
+    fn empty_postings_are_balanced() {
+        assert!(Postings::new().is_balanced());
+    }
+}
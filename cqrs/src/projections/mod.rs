@@ -1,11 +1,1091 @@
-use std::collections::HashSet;
-use crate::{write::ledger::LedgerId, Event};
+use crate::{error::LifecycleError, write::ledger::LedgerId, Category, Event, Name, Number};
+use chrono::prelude::*;
+use personal_finance::{
+    balance::NetBalance,
+    entry::{Account, Chart},
+    ledger::Ledger as AccountLedger,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub fn ledger_ids(mut state: HashSet<LedgerId>, item: &Event) -> HashSet<LedgerId> {
+    if let Event::LedgerCreated { id } = item {
+        state.insert(id.clone());
+    }
+
+    state
+}
+
+/// The chart of accounts of every ledger
+///
+/// Closed accounts are kept in the chart but marked as inactive.
+pub fn charts(mut state: HashMap<LedgerId, Chart>, item: &Event) -> HashMap<LedgerId, Chart> {
     match item {
-        Event::LedgerCreated{ id } => { state.insert(id.clone()); },
+        Event::LedgerCreated { id } => {
+            state.entry(id.clone()).or_default();
+        }
+        Event::AccountOpened {
+            ledger,
+            id,
+            name,
+            category,
+        } => {
+            state
+                .entry(ledger.clone())
+                .or_default()
+                .insert(Account::new(*id, name.clone(), *category));
+        }
+        Event::AccountClosed { ledger, account } => {
+            if let Some(chart) = state.get_mut(ledger) {
+                if let Some(closed) = chart.get(*account).cloned() {
+                    chart.insert(closed.with_active(false));
+                }
+            }
+        }
+        Event::AccountRecategorized {
+            ledger,
+            account,
+            category,
+        } => {
+            if let Some(chart) = state.get_mut(ledger) {
+                if let Some(recategorized) = chart.get(*account).cloned() {
+                    chart.insert(recategorized.with_category(*category));
+                }
+            }
+        }
         _ => {}
     }
 
     state
 }
+
+/// Every account of every ledger together with whether it is still open
+///
+/// Sorted by ledger and then by account number.
+pub fn all_accounts<'a, I>(events: I) -> Vec<(LedgerId, Number, Name, Category, bool)>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    let mut accounts = events
+        .into_iter()
+        .fold(HashMap::new(), charts)
+        .into_iter()
+        .flat_map(|(ledger, chart)| {
+            chart.into_iter().map(move |account| {
+                (
+                    ledger.clone(),
+                    account.number(),
+                    account.name().clone(),
+                    account.category(),
+                    account.is_active(),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    accounts.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+
+    accounts
+}
+
+/// Balances of all accounts where debits are positive and credits are negative
+///
+/// The balances are grouped by the latest category of each account.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrialBalance {
+    categories: HashMap<(LedgerId, Number), Category>,
+    names: HashMap<(LedgerId, Number), Name>,
+    balances: HashMap<(LedgerId, Number), NetBalance>,
+}
+
+impl TrialBalance {
+    pub fn balance(&self, ledger: &LedgerId, account: Number) -> i64 {
+        self.balances
+            .get(&(ledger.clone(), account))
+            .map(NetBalance::value)
+            .unwrap_or_default()
+    }
+
+    pub fn category(&self, ledger: &LedgerId, account: Number) -> Option<Category> {
+        self.categories.get(&(ledger.clone(), account)).copied()
+    }
+
+    /// Total balance of each category
+    pub fn by_category(&self) -> BTreeMap<Category, i64> {
+        self.balances
+            .iter()
+            .filter_map(|(key, balance)| self.categories.get(key).map(|c| (*c, *balance)))
+            .fold(BTreeMap::new(), |mut totals, (category, balance)| {
+                *totals.entry(category).or_insert(NetBalance::ZERO) += balance;
+                totals
+            })
+            .into_iter()
+            .map(|(category, total)| (category, total.value()))
+            .collect()
+    }
+}
+
+pub fn trial_balance(mut state: TrialBalance, item: &Event) -> TrialBalance {
+    match item {
+        Event::AccountOpened {
+            ledger,
+            id,
+            name,
+            category,
+        } => {
+            state.categories.insert((ledger.clone(), *id), *category);
+            state.names.insert((ledger.clone(), *id), name.clone());
+        }
+        Event::AccountRecategorized {
+            ledger,
+            account,
+            category,
+        } => {
+            state
+                .categories
+                .insert((ledger.clone(), *account), *category);
+        }
+        Event::Transaction {
+            ledger,
+            transactions,
+            ..
+        } => {
+            for posting in transactions {
+                *state
+                    .balances
+                    .entry((ledger.clone(), posting.account()))
+                    .or_insert(NetBalance::ZERO) += *posting.amount();
+            }
+        }
+        _ => {}
+    }
+
+    state
+}
+
+/// Build the read side ledger of an account from the transactions in a ledger
+///
+/// This bridges the event stream with [personal_finance::ledger::Ledger], the
+/// postings are matched by account number only.
+pub fn account_ledger<'a, 'e, I>(
+    account: &'a Account,
+    ledger: &LedgerId,
+    events: I,
+) -> AccountLedger<'a>
+where
+    I: IntoIterator<Item = &'e Event>,
+{
+    let mut account_ledger = AccountLedger::new(account);
+    account_ledger.extend(
+        events
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::Transaction {
+                    ledger: id,
+                    date,
+                    transactions,
+                    ..
+                } if id == ledger => Some((date, transactions)),
+                _ => None,
+            })
+            .flat_map(|(date, transactions)| {
+                transactions
+                    .iter()
+                    .filter(|posting| posting.account() == account.number())
+                    .map(|posting| (*date, *posting.amount()))
+            }),
+    );
+
+    account_ledger
+}
+
+/// Group the transactions of a ledger by their date
+///
+/// Transactions on the same date keep the order they were appended in.
+pub fn transactions_by_date<'a, I>(
+    events: I,
+    ledger: &LedgerId,
+) -> BTreeMap<Date<Utc>, Vec<&'a Event>>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    events
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::Transaction {
+                ledger: id, date, ..
+            } if id == ledger => Some((*date, event)),
+            _ => None,
+        })
+        .fold(BTreeMap::new(), |mut dates, (date, event)| {
+            dates.entry(date).or_insert_with(Vec::new).push(event);
+            dates
+        })
+}
+
+/// Transactions tagged with `key` set to `value` in their metadata
+pub fn transactions_tagged<'a, I>(events: I, key: &str, value: &str) -> Vec<&'a Event>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    events
+        .into_iter()
+        .filter(|event| match event {
+            Event::Transaction { metadata, .. } => {
+                metadata.get(key).map(String::as_str) == Some(value)
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+/// Check that the accounts of a ledger are opened and closed in order and that
+/// no transaction is posted to an account while it is closed
+///
+/// Events carry no timestamp besides the date of a transaction, so an account
+/// is closed from the position of its close event until it is opened again.
+/// Positions are indexes into `events`. All issues are returned, not just the first.
+pub fn validate_account_lifecycle<'a, I>(
+    events: I,
+    ledger: &LedgerId,
+) -> Result<(), Vec<LifecycleError>>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    // true while open, false once closed
+    let mut accounts = HashMap::<Number, bool>::new();
+    let mut errors = Vec::new();
+
+    for (position, event) in events.into_iter().enumerate() {
+        match event {
+            Event::AccountOpened {
+                ledger: id,
+                id: account,
+                ..
+            } if id == ledger => {
+                let was_open = accounts.insert(*account, true) == Some(true);
+                if was_open {
+                    errors.push(LifecycleError::AlreadyOpen {
+                        position,
+                        account: *account,
+                    });
+                }
+            }
+            Event::AccountClosed {
+                ledger: id,
+                account,
+            } if id == ledger => {
+                let was_open = accounts.insert(*account, false) == Some(true);
+                if !was_open {
+                    errors.push(LifecycleError::CloseWithoutOpen {
+                        position,
+                        account: *account,
+                    });
+                }
+            }
+            Event::Transaction {
+                ledger: id,
+                transactions,
+                ..
+            } if id == ledger => {
+                errors.extend(transactions.iter().filter_map(|posting| {
+                    let account = posting.account();
+                    match accounts.get(&account) {
+                        Some(true) => None,
+                        Some(false) => {
+                            Some(LifecycleError::PostedWhileClosed { position, account })
+                        }
+                        None => Some(LifecycleError::PostedBeforeOpen { position, account }),
+                    }
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Counts of the events of a ledger
+///
+/// Events don't carry a timestamp so there is no record of when a ledger was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LedgerSummary {
+    /// Number of distinct accounts ever opened, a reopened account counts once
+    pub account_count: usize,
+    /// Number of accounts opened and not closed
+    pub open_accounts: usize,
+    pub transaction_count: usize,
+}
+
+/// Summarize every ledger in a single pass over the events
+pub fn ledger_summary<'a, I>(events: I) -> HashMap<LedgerId, LedgerSummary>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    // The accounts ever opened and the accounts currently open of each ledger
+    type Accounts = (HashSet<Number>, HashSet<Number>);
+
+    events
+        .into_iter()
+        .fold(
+            HashMap::<LedgerId, (Accounts, usize)>::new(),
+            |mut ledgers, event| {
+                match event {
+                    Event::LedgerCreated { id } => {
+                        ledgers.entry(id.clone()).or_default();
+                    }
+                    Event::AccountOpened { ledger, id, .. } => {
+                        let ((opened, open), _) = ledgers.entry(ledger.clone()).or_default();
+                        opened.insert(*id);
+                        open.insert(*id);
+                    }
+                    Event::AccountClosed { ledger, account } => {
+                        let ((_, open), _) = ledgers.entry(ledger.clone()).or_default();
+                        open.remove(account);
+                    }
+                    Event::Transaction { ledger, .. } => {
+                        ledgers.entry(ledger.clone()).or_default().1 += 1;
+                    }
+                    _ => {}
+                }
+
+                ledgers
+            },
+        )
+        .into_iter()
+        .map(|(ledger, ((opened, open), transaction_count))| {
+            let summary = LedgerSummary {
+                account_count: opened.len(),
+                open_accounts: open.len(),
+                transaction_count,
+            };
+            (ledger, summary)
+        })
+        .collect()
+}
+
+/// The balance of one account in a report, debits are positive and credits are negative
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportLine {
+    pub number: Number,
+    pub name: Name,
+    pub amount: i64,
+}
+
+#[cfg(feature = "serde")]
+fn report_lines_json(lines: &[ReportLine]) -> serde_json::Value {
+    lines
+        .iter()
+        .map(|line| {
+            serde_json::json!({
+                "number": line.number.number(),
+                "name": line.name.as_str(),
+                "amount": line.amount,
+            })
+        })
+        .collect()
+}
+
+/// Version of the JSON shape written by the `to_json` methods of reports
+pub const REPORT_JSON_VERSION: u32 = 1;
+
+/// Category totals of one or more ledgers where debits are positive and credits are negative
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceSheet {
+    totals: BTreeMap<Category, NetBalance>,
+    lines: BTreeMap<Category, Vec<ReportLine>>,
+}
+
+impl BalanceSheet {
+    pub fn total(&self, category: Category) -> i64 {
+        self.totals
+            .get(&category)
+            .map(NetBalance::value)
+            .unwrap_or_default()
+    }
+
+    /// Balances of the accounts of a category ordered by account number
+    pub fn lines(&self, category: Category) -> &[ReportLine] {
+        self.lines
+            .get(&category)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Render the balance sheet for a frontend
+    ///
+    /// The shape is kept stable and a change of it bumps [REPORT_JSON_VERSION]:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "assets": [{ "number": 101, "name": "Cash", "amount": 1500 }],
+    ///   "liabilities": [],
+    ///   "equity": [{ "number": 301, "name": "Capital", "amount": -1500 }],
+    ///   "totals": { "assets": 1500, "liabilities": 0, "equity": -1500 }
+    /// }
+    /// ```
+    ///
+    /// Amounts are debit positive and credit negative like everywhere else.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": REPORT_JSON_VERSION,
+            "assets": report_lines_json(self.lines(Category::Asset)),
+            "liabilities": report_lines_json(self.lines(Category::Liability)),
+            "equity": report_lines_json(self.lines(Category::Equity)),
+            "totals": {
+                "assets": self.total(Category::Asset),
+                "liabilities": self.total(Category::Liability),
+                "equity": self.total(Category::Equity),
+            },
+        })
+    }
+}
+
+/// Income and expenses of a ledger over a period, debits are positive and credits are negative
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncomeStatement {
+    pub income: Vec<ReportLine>,
+    pub expenses: Vec<ReportLine>,
+}
+
+impl IncomeStatement {
+    /// The result of the period, a profit is negative since income is credited
+    pub fn net(&self) -> i64 {
+        self.income
+            .iter()
+            .chain(&self.expenses)
+            .map(|line| line.amount)
+            .sum()
+    }
+
+    /// Render the income statement for a frontend
+    ///
+    /// The shape is kept stable and a change of it bumps [REPORT_JSON_VERSION]:
+    ///
+    /// ```json
+    /// {
+    ///   "version": 1,
+    ///   "income": [{ "number": 401, "name": "Salary", "amount": -2000 }],
+    ///   "expenses": [{ "number": 501, "name": "Groceries", "amount": 300 }],
+    ///   "totals": { "income": -2000, "expenses": 300, "net": -1700 }
+    /// }
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Value {
+        let total = |lines: &[ReportLine]| lines.iter().map(|line| line.amount).sum::<i64>();
+
+        serde_json::json!({
+            "version": REPORT_JSON_VERSION,
+            "income": report_lines_json(&self.income),
+            "expenses": report_lines_json(&self.expenses),
+            "totals": {
+                "income": total(&self.income),
+                "expenses": total(&self.expenses),
+                "net": self.net(),
+            },
+        })
+    }
+}
+
+/// Income and expenses of a ledger from transactions dated within `from` and `to`
+pub fn income_statement<'a, I>(
+    events: I,
+    ledger: &LedgerId,
+    from: Date<Utc>,
+    to: Date<Utc>,
+) -> IncomeStatement
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    let trial_balance = events
+        .into_iter()
+        .filter(|event| match event {
+            Event::Transaction {
+                ledger: id, date, ..
+            } => id == ledger && from <= *date && *date <= to,
+            _ => true,
+        })
+        .fold(TrialBalance::default(), trial_balance);
+
+    let mut lines = report_lines(&trial_balance, |key| key.0 == *ledger);
+
+    IncomeStatement {
+        income: lines.remove(&Category::Income).unwrap_or_default(),
+        expenses: lines.remove(&Category::Expenses).unwrap_or_default(),
+    }
+}
+
+/// Account balances of a trial balance grouped by category
+///
+/// Accounts with the same number in different ledgers are combined into one
+/// line named after the account in the first ledger.
+fn report_lines<F>(trial_balance: &TrialBalance, include: F) -> BTreeMap<Category, Vec<ReportLine>>
+where
+    F: Fn(&(LedgerId, Number)) -> bool,
+{
+    let mut keys = trial_balance
+        .balances
+        .keys()
+        .filter(|key| include(key))
+        .collect::<Vec<_>>();
+    keys.sort();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let category = trial_balance.categories.get(key)?;
+            let name = trial_balance.names.get(key)?;
+            Some((*category, key.1, name, trial_balance.balances[key]))
+        })
+        .fold(
+            BTreeMap::<Category, BTreeMap<Number, ReportLine>>::new(),
+            |mut lines, (category, number, name, balance)| {
+                lines
+                    .entry(category)
+                    .or_default()
+                    .entry(number)
+                    .or_insert_with(|| ReportLine {
+                        number,
+                        name: name.clone(),
+                        amount: 0,
+                    })
+                    .amount += balance.value();
+                lines
+            },
+        )
+        .into_iter()
+        .map(|(category, lines)| (category, lines.into_values().collect()))
+        .collect()
+}
+
+/// An account in a specific ledger
+pub type LedgerAccount = (LedgerId, Number);
+
+/// Sum the category totals of several ledgers as of a date
+///
+/// Each pair in `eliminations` is an inter-ledger balance, like a receivable in
+/// one ledger and the matching payable in another. The balances of both accounts
+/// are left out of the consolidated totals. The account lines of the ledgers are
+/// combined by account number.
+pub fn consolidated_balance_sheet<'a, I>(
+    events: I,
+    ledgers: &[LedgerId],
+    as_of: Date<Utc>,
+    eliminations: &[(LedgerAccount, LedgerAccount)],
+) -> BalanceSheet
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    let trial_balance = events
+        .into_iter()
+        .filter(|event| match event {
+            Event::Transaction { date, .. } => *date <= as_of,
+            _ => true,
+        })
+        .fold(TrialBalance::default(), trial_balance);
+
+    let eliminated = eliminations
+        .iter()
+        .flat_map(|(left, right)| [left, right])
+        .collect::<HashSet<_>>();
+
+    let include = |key: &(LedgerId, Number)| ledgers.contains(&key.0) && !eliminated.contains(key);
+    let totals = trial_balance
+        .balances
+        .iter()
+        .filter(|(key, _)| include(key))
+        .filter_map(|(key, balance)| {
+            trial_balance
+                .categories
+                .get(key)
+                .map(|category| (*category, *balance))
+        })
+        .fold(BTreeMap::new(), |mut totals, (category, balance)| {
+            *totals.entry(category).or_insert(NetBalance::ZERO) += balance;
+            totals
+        });
+    let lines = report_lines(&trial_balance, include);
+
+    BalanceSheet { totals, lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        events::{projections::Projection, EventPointer},
+        Balance, Ledger, Posting,
+    };
+
+    #[test]
+    fn trial_balance_should_use_latest_category() {
+        let id = LedgerId::new("2014").unwrap();
+        let mut ledger = Ledger::new(
+            id.clone(),
+            &[Event::new(Event::LedgerCreated { id: id.clone() })],
+        )
+        .unwrap();
+
+        let bank = Number::new(101).unwrap();
+        let misclassified = Number::new(501).unwrap();
+        let mut events = Vec::new();
+        events.extend_from_slice(
+            ledger
+                .open_account(bank, Name::new("Bank").unwrap(), Category::Asset)
+                .unwrap(),
+        );
+        events.extend_from_slice(
+            ledger
+                .open_account(
+                    misclassified,
+                    Name::new("Loan").unwrap(),
+                    Category::Expenses,
+                )
+                .unwrap(),
+        );
+        events.extend_from_slice(
+            ledger
+                .transaction(
+                    "Loan",
+                    vec![
+                        Posting::debit(bank, 500).unwrap(),
+                        Posting::credit(misclassified, 500).unwrap(),
+                    ]
+                    .into(),
+                    Utc.ymd(2014, 4, 1),
+                )
+                .unwrap(),
+        );
+        events.extend_from_slice(
+            ledger
+                .recategorize_account(misclassified, Category::Liability)
+                .unwrap(),
+        );
+
+        let actual = Projection::new(TrialBalance::default(), trial_balance)
+            .project(events.iter().map(|x| &**x));
+
+        assert_eq!(
+            actual.category(&id, misclassified),
+            Some(Category::Liability)
+        );
+        assert_eq!(
+            actual.by_category(),
+            BTreeMap::from([(Category::Asset, 500), (Category::Liability, -500)])
+        );
+    }
+
+    fn open(ledger: &LedgerId, number: u32, category: Category) -> Event {
+        Event::AccountOpened {
+            ledger: ledger.clone(),
+            id: Number::new(number).unwrap(),
+            name: Name::new("Account").unwrap(),
+            category,
+        }
+    }
+
+    fn transfer(ledger: &LedgerId, debit: u32, credit: u32, amount: u32, date: Date<Utc>) -> Event {
+        Event::Transaction {
+            ledger: ledger.clone(),
+            description: String::from("Transfer"),
+            date,
+            transactions: vec![
+                Posting::debit(Number::new(debit).unwrap(), amount).unwrap(),
+                Posting::credit(Number::new(credit).unwrap(), amount).unwrap(),
+            ],
+            metadata: Default::default(),
+        }
+    }
+
+    #[test]
+    fn consolidated_balance_sheet_should_combine_ledgers_and_eliminate_pairs() {
+        let parent = LedgerId::new("parent").unwrap();
+        let subsidiary = LedgerId::new("subsidiary").unwrap();
+        let other = LedgerId::new("other").unwrap();
+
+        let events = vec![
+            Event::LedgerCreated { id: parent.clone() },
+            Event::LedgerCreated {
+                id: subsidiary.clone(),
+            },
+            Event::LedgerCreated { id: other.clone() },
+            open(&parent, 101, Category::Asset),
+            open(&parent, 102, Category::Asset),
+            open(&parent, 301, Category::Equity),
+            open(&subsidiary, 101, Category::Asset),
+            open(&subsidiary, 201, Category::Liability),
+            open(&subsidiary, 301, Category::Equity),
+            open(&other, 101, Category::Asset),
+            open(&other, 301, Category::Equity),
+            transfer(&parent, 101, 301, 1000, Utc.ymd(2014, 1, 1)),
+            transfer(&subsidiary, 101, 301, 500, Utc.ymd(2014, 1, 1)),
+            // the parent lends 200 to the subsidiary
+            transfer(&parent, 102, 101, 200, Utc.ymd(2014, 2, 1)),
+            transfer(&subsidiary, 101, 201, 200, Utc.ymd(2014, 2, 1)),
+            // after the balance sheet date
+            transfer(&parent, 101, 301, 50, Utc.ymd(2014, 4, 1)),
+            transfer(&other, 101, 301, 700, Utc.ymd(2014, 1, 1)),
+        ];
+
+        let actual = consolidated_balance_sheet(
+            &events,
+            &[parent.clone(), subsidiary.clone()],
+            Utc.ymd(2014, 3, 31),
+            &[(
+                (parent, Number::new(102).unwrap()),
+                (subsidiary, Number::new(201).unwrap()),
+            )],
+        );
+
+        assert_eq!(actual.total(Category::Asset), 1500);
+        assert_eq!(actual.total(Category::Liability), 0);
+        assert_eq!(actual.total(Category::Equity), -1500);
+    }
+
+    fn small_book(ledger: &LedgerId) -> Vec<Event> {
+        let open = |number, name, category| {
+            Event::account_opened(ledger.clone(), number, name, category).unwrap()
+        };
+
+        vec![
+            Event::LedgerCreated { id: ledger.clone() },
+            open(101, "Cash", Category::Asset),
+            open(301, "Capital", Category::Equity),
+            open(401, "Salary", Category::Income),
+            open(501, "Groceries", Category::Expenses),
+            transfer(ledger, 101, 301, 1000, Utc.ymd(2014, 1, 1)),
+            transfer(ledger, 101, 401, 2000, Utc.ymd(2014, 1, 25)),
+            transfer(ledger, 501, 101, 300, Utc.ymd(2014, 2, 3)),
+            transfer(ledger, 501, 101, 50, Utc.ymd(2014, 3, 1)),
+        ]
+    }
+
+    #[test]
+    fn income_statement_should_only_include_the_period() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let events = small_book(&ledger);
+
+        let actual = income_statement(&events, &ledger, Utc.ymd(2014, 1, 1), Utc.ymd(2014, 2, 28));
+
+        assert_eq!(actual.income.len(), 1);
+        assert_eq!(actual.income[0].amount, -2000);
+        assert_eq!(actual.expenses[0].amount, 300);
+        assert_eq!(actual.net(), -1700);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn balance_sheet_to_json_has_a_stable_shape() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let events = small_book(&ledger);
+
+        let actual = consolidated_balance_sheet(&events, &[ledger], Utc.ymd(2014, 2, 28), &[]);
+
+        assert_eq!(
+            actual.to_json(),
+            serde_json::json!({
+                "version": 1,
+                "assets": [{ "number": 101, "name": "Cash", "amount": 2700 }],
+                "liabilities": [],
+                "equity": [{ "number": 301, "name": "Capital", "amount": -1000 }],
+                "totals": { "assets": 2700, "liabilities": 0, "equity": -1000 },
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn income_statement_to_json_has_a_stable_shape() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let events = small_book(&ledger);
+
+        let actual = income_statement(&events, &ledger, Utc.ymd(2014, 1, 1), Utc.ymd(2014, 2, 28));
+
+        assert_eq!(
+            actual.to_json(),
+            serde_json::json!({
+                "version": 1,
+                "income": [{ "number": 401, "name": "Salary", "amount": -2000 }],
+                "expenses": [{ "number": 501, "name": "Groceries", "amount": 300 }],
+                "totals": { "income": -2000, "expenses": 300, "net": -1700 },
+            })
+        );
+    }
+
+    #[test]
+    fn ledger_summary_should_count_per_ledger() {
+        let first = LedgerId::new("2014").unwrap();
+        let second = LedgerId::new("2015").unwrap();
+
+        let events = vec![
+            Event::LedgerCreated { id: first.clone() },
+            Event::LedgerCreated { id: second.clone() },
+            open(&first, 101, Category::Asset),
+            open(&first, 301, Category::Equity),
+            open(&first, 102, Category::Asset),
+            open(&second, 101, Category::Asset),
+            transfer(&first, 101, 301, 100, Utc.ymd(2014, 1, 1)),
+            transfer(&first, 102, 101, 50, Utc.ymd(2014, 1, 2)),
+            Event::AccountClosed {
+                ledger: first.clone(),
+                account: Number::new(102).unwrap(),
+            },
+            Event::AccountClosed {
+                ledger: second.clone(),
+                account: Number::new(101).unwrap(),
+            },
+            open(&second, 101, Category::Asset),
+        ];
+
+        let actual = ledger_summary(&events);
+
+        assert_eq!(
+            actual,
+            HashMap::from([
+                (
+                    first,
+                    LedgerSummary {
+                        account_count: 3,
+                        open_accounts: 2,
+                        transaction_count: 2,
+                    }
+                ),
+                (
+                    second,
+                    LedgerSummary {
+                        account_count: 1,
+                        open_accounts: 1,
+                        transaction_count: 0,
+                    }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn transactions_by_date_should_group_in_append_order() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let other = LedgerId::new("2015").unwrap();
+
+        let events = vec![
+            Event::LedgerCreated { id: ledger.clone() },
+            transfer(&ledger, 101, 301, 1, Utc.ymd(2014, 1, 3)),
+            transfer(&ledger, 101, 301, 2, Utc.ymd(2014, 1, 1)),
+            transfer(&other, 101, 301, 3, Utc.ymd(2014, 1, 1)),
+            transfer(&ledger, 101, 301, 4, Utc.ymd(2014, 1, 3)),
+            transfer(&ledger, 101, 301, 5, Utc.ymd(2014, 1, 2)),
+            transfer(&ledger, 101, 301, 6, Utc.ymd(2014, 1, 1)),
+        ];
+
+        let actual = transactions_by_date(&events, &ledger);
+
+        assert_eq!(
+            actual,
+            BTreeMap::from([
+                (Utc.ymd(2014, 1, 1), vec![&events[2], &events[6]]),
+                (Utc.ymd(2014, 1, 2), vec![&events[5]]),
+                (Utc.ymd(2014, 1, 3), vec![&events[1], &events[4]]),
+            ])
+        );
+    }
+
+    #[test]
+    fn transactions_tagged_should_filter_on_one_metadata_key() {
+        let id = LedgerId::new("2014").unwrap();
+        let mut ledger = Ledger::new(
+            id.clone(),
+            &[
+                Event::new(Event::LedgerCreated { id: id.clone() }),
+                Event::new(open(&id, 101, Category::Asset)),
+                Event::new(open(&id, 401, Category::Income)),
+            ],
+        )
+        .unwrap();
+
+        let postings = || {
+            vec![
+                Posting::debit(Number::new(101).unwrap(), 100).unwrap(),
+                Posting::credit(Number::new(401).unwrap(), 100).unwrap(),
+            ]
+        };
+        let mut events = Vec::new();
+        events.extend_from_slice(
+            ledger
+                .transaction_with_metadata(
+                    "Consulting",
+                    postings().into(),
+                    Utc.ymd(2014, 4, 1),
+                    BTreeMap::from([
+                        (String::from("project"), String::from("apollo")),
+                        (String::from("client"), String::from("42")),
+                    ]),
+                )
+                .unwrap(),
+        );
+        events.extend_from_slice(
+            ledger
+                .transaction("Untagged", postings().into(), Utc.ymd(2014, 4, 2))
+                .unwrap(),
+        );
+        let events = events.iter().map(|x| &**x).collect::<Vec<_>>();
+
+        let actual = transactions_tagged(events.iter().copied(), "client", "42");
+
+        assert_eq!(actual, vec![events[0]]);
+        assert!(matches!(
+            actual[0],
+            Event::Transaction { metadata, .. } if metadata.get("project") == Some(&String::from("apollo"))
+        ));
+        assert!(transactions_tagged(events.iter().copied(), "client", "7").is_empty());
+    }
+
+    #[test]
+    fn validate_account_lifecycle_should_flag_posting_to_closed_account() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let other = LedgerId::new("2015").unwrap();
+        let close = |ledger: &LedgerId, number| Event::AccountClosed {
+            ledger: ledger.clone(),
+            account: Number::new(number).unwrap(),
+        };
+
+        let events = [
+            Event::LedgerCreated { id: ledger.clone() },
+            open(&ledger, 101, Category::Asset),
+            open(&ledger, 501, Category::Expenses),
+            transfer(&ledger, 501, 101, 10, Utc.ymd(2014, 1, 1)),
+            close(&ledger, 501),
+            transfer(&ledger, 501, 101, 20, Utc.ymd(2014, 2, 1)),
+            open(&ledger, 501, Category::Expenses),
+            transfer(&ledger, 501, 101, 30, Utc.ymd(2014, 3, 1)),
+            close(&other, 501),
+        ];
+
+        assert_eq!(
+            validate_account_lifecycle(&events, &ledger),
+            Err(vec![LifecycleError::PostedWhileClosed {
+                position: 5,
+                account: Number::new(501).unwrap(),
+            }])
+        );
+    }
+
+    #[test]
+    fn validate_account_lifecycle_should_return_all_issues() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let events = [
+            Event::LedgerCreated { id: ledger.clone() },
+            open(&ledger, 101, Category::Asset),
+            open(&ledger, 101, Category::Asset),
+            Event::AccountClosed {
+                ledger: ledger.clone(),
+                account: Number::new(301).unwrap(),
+            },
+            transfer(&ledger, 501, 101, 10, Utc.ymd(2014, 1, 1)),
+        ];
+
+        assert_eq!(
+            validate_account_lifecycle(&events, &ledger),
+            Err(vec![
+                LifecycleError::AlreadyOpen {
+                    position: 2,
+                    account: Number::new(101).unwrap(),
+                },
+                LifecycleError::CloseWithoutOpen {
+                    position: 3,
+                    account: Number::new(301).unwrap(),
+                },
+                LifecycleError::PostedBeforeOpen {
+                    position: 4,
+                    account: Number::new(501).unwrap(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn charts_should_mark_closed_accounts_inactive() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let events = [
+            Event::LedgerCreated { id: ledger.clone() },
+            open(&ledger, 101, Category::Asset),
+            open(&ledger, 102, Category::Asset),
+            Event::AccountClosed {
+                ledger: ledger.clone(),
+                account: Number::new(102).unwrap(),
+            },
+        ];
+
+        let actual = Projection::new(HashMap::new(), charts).project(events.iter());
+
+        let chart = &actual[&ledger];
+        assert!(chart.get(Number::new(101).unwrap()).unwrap().is_active());
+        assert!(!chart.get(Number::new(102).unwrap()).unwrap().is_active());
+    }
+
+    #[test]
+    fn all_accounts_should_list_every_ledger_sorted() {
+        let first = LedgerId::new("2014").unwrap();
+        let second = LedgerId::new("2015").unwrap();
+        let events = [
+            Event::LedgerCreated { id: second.clone() },
+            Event::LedgerCreated { id: first.clone() },
+            open(&second, 301, Category::Equity),
+            open(&first, 501, Category::Expenses),
+            open(&first, 101, Category::Asset),
+            Event::AccountClosed {
+                ledger: first.clone(),
+                account: Number::new(501).unwrap(),
+            },
+        ];
+
+        let actual = all_accounts(&events)
+            .into_iter()
+            .map(|(ledger, number, _, category, open)| (ledger, number.number(), category, open))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            actual,
+            vec![
+                (first.clone(), 101, Category::Asset, true),
+                (first, 501, Category::Expenses, false),
+                (second, 301, Category::Equity, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn account_ledger_should_contain_postings_of_the_account() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let other = LedgerId::new("2015").unwrap();
+        let events = [
+            Event::LedgerCreated { id: ledger.clone() },
+            open(&ledger, 101, Category::Asset),
+            open(&ledger, 102, Category::Asset),
+            open(&ledger, 301, Category::Equity),
+            transfer(&ledger, 101, 301, 1000, Utc.ymd(2014, 1, 1)),
+            transfer(&ledger, 102, 101, 200, Utc.ymd(2014, 1, 5)),
+            transfer(&ledger, 102, 301, 50, Utc.ymd(2014, 1, 6)),
+            transfer(&other, 101, 301, 700, Utc.ymd(2014, 1, 1)),
+        ];
+        let account = Account::new(
+            Number::new(101).unwrap(),
+            Name::new("Bank").unwrap(),
+            Category::Asset,
+        );
+
+        let actual = account_ledger(&account, &ledger, &events);
+
+        assert_eq!(
+            actual.iter().collect::<Vec<_>>(),
+            vec![
+                (&Utc.ymd(2014, 1, 1), &Balance::debit(1000).unwrap()),
+                (&Utc.ymd(2014, 1, 5), &Balance::credit(200).unwrap()),
+            ]
+        );
+    }
+}
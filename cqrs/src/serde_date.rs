@@ -0,0 +1,12 @@
+//! Serialize a `Date<Utc>` as an ISO 8601 calendar date, e.g. `2014-04-01`.
+
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S: Serializer>(date: &Date<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    date.naive_utc().serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date<Utc>, D::Error> {
+    NaiveDate::deserialize(deserializer).map(|date| Date::from_utc(date, Utc))
+}
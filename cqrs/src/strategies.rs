@@ -0,0 +1,191 @@
+//! Proptest strategies for generating events and valid event histories.
+
+use chrono::prelude::*;
+use proptest::{prelude::*, sample::Index};
+use std::collections::BTreeMap;
+
+use crate::{write::ledger::LedgerId, Balance, Category, Event, Name, Number, Posting};
+
+pub fn ledger_id() -> impl Strategy<Value = LedgerId> {
+    "[a-zA-Z0-9][a-zA-Z0-9_-]{0,15}".prop_map(|id| LedgerId::new(&id).unwrap())
+}
+
+pub fn number() -> impl Strategy<Value = Number> {
+    (1..10_000u32).prop_map(|x| Number::new(x).unwrap())
+}
+
+pub fn name() -> impl Strategy<Value = Name> {
+    "[a-zA-Z][a-zA-Z0-9 ]{0,20}".prop_map(|name| Name::new(name).unwrap())
+}
+
+pub fn category() -> impl Strategy<Value = Category> {
+    prop_oneof![
+        Just(Category::Asset),
+        Just(Category::Liability),
+        Just(Category::Equity),
+        Just(Category::Income),
+        Just(Category::Expenses),
+    ]
+}
+
+/// Amounts are kept small enough that summing a history won't overflow
+pub fn balance() -> impl Strategy<Value = Balance> {
+    (any::<bool>(), 1..100_000u32).prop_map(|(is_debit, amount)| {
+        if is_debit {
+            Balance::debit(amount).unwrap()
+        } else {
+            Balance::credit(amount).unwrap()
+        }
+    })
+}
+
+pub fn date() -> impl Strategy<Value = Date<Utc>> {
+    (2000..2030i32, 1..=12u32, 1..=28u32).prop_map(|(y, m, d)| Utc.ymd(y, m, d))
+}
+
+pub fn posting() -> impl Strategy<Value = Posting> {
+    (number(), balance()).prop_map(Posting::from)
+}
+
+/// Any single event, there is no guarantee that a sequence of these are valid
+pub fn event() -> impl Strategy<Value = Event> {
+    prop_oneof![
+        ledger_id().prop_map(|id| Event::LedgerCreated { id }),
+        (ledger_id(), number(), name(), category()).prop_map(|(ledger, id, name, category)| {
+            Event::AccountOpened {
+                ledger,
+                id,
+                name,
+                category,
+            }
+        }),
+        (ledger_id(), number())
+            .prop_map(|(ledger, account)| Event::AccountClosed { ledger, account }),
+        (ledger_id(), number(), category()).prop_map(|(ledger, account, category)| {
+            Event::AccountRecategorized {
+                ledger,
+                account,
+                category,
+            }
+        }),
+        (
+            ledger_id(),
+            "[a-zA-Z ]{0,20}",
+            date(),
+            prop::collection::vec(posting(), 1..5),
+            prop::collection::btree_map("[a-z]{1,8}", "[a-zA-Z0-9 ]{0,12}", 0..3)
+        )
+            .prop_map(|(ledger, description, date, transactions, metadata)| {
+                Event::Transaction {
+                    ledger,
+                    description,
+                    date,
+                    transactions,
+                    metadata,
+                }
+            }),
+        (ledger_id(), date()).prop_map(|(ledger, up_to)| Event::PeriodSealed { ledger, up_to }),
+        (ledger_id(), "[a-zA-Z ]{0,20}")
+            .prop_map(|(ledger, reason)| Event::CommandRejected { ledger, reason }),
+    ]
+}
+
+/// A causally valid history of a single ledger
+///
+/// The ledger is created first, then all accounts are opened and lastly
+/// balanced transactions between the opened accounts are posted.
+pub fn valid_ledger_history() -> impl Strategy<Value = Vec<Event>> {
+    let accounts = prop::collection::btree_map(number(), (name(), category()), 1..8);
+    let transactions = prop::collection::vec(
+        (
+            date(),
+            prop::collection::vec((any::<Index>(), any::<Index>(), 1..100_000u32), 1..4),
+        ),
+        0..10,
+    );
+
+    (ledger_id(), accounts, transactions).prop_map(|(ledger, accounts, transactions)| {
+        let numbers = accounts.keys().copied().collect::<Vec<_>>();
+
+        let mut history = vec![Event::LedgerCreated { id: ledger.clone() }];
+        history.extend(
+            accounts
+                .into_iter()
+                .map(|(id, (name, category))| Event::AccountOpened {
+                    ledger: ledger.clone(),
+                    id,
+                    name,
+                    category,
+                }),
+        );
+        history.extend(transactions.into_iter().map(|(date, lines)| {
+            let transactions = lines
+                .into_iter()
+                .flat_map(|(debit, credit, amount)| {
+                    [
+                        Posting::debit(*debit.get(&numbers), amount).unwrap(),
+                        Posting::credit(*credit.get(&numbers), amount).unwrap(),
+                    ]
+                })
+                .collect();
+
+            Event::Transaction {
+                ledger: ledger.clone(),
+                description: String::from("Generated"),
+                date,
+                transactions,
+                metadata: BTreeMap::new(),
+            }
+        }));
+
+        history
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::events::EventPointer;
+
+    proptest! {
+        #[test]
+        fn replaying_valid_history_yields_balanced_trial_balance(history in valid_ledger_history()) {
+            let id = match &history[0] {
+                Event::LedgerCreated { id } => id.clone(),
+                _ => unreachable!(),
+            };
+            let mut ledger = crate::Ledger::new(id.clone(), &[Event::new(history[0].clone())]).unwrap();
+            for event in &history[1..] {
+                match event.clone() {
+                    Event::AccountOpened { id, name, category, .. } => {
+                        prop_assert!(ledger.open_account(id, name, category).is_ok());
+                    }
+                    Event::Transaction { description, date, transactions, .. } => {
+                        prop_assert!(ledger.transaction(description, transactions.into(), date).is_ok());
+                    }
+                    _ => unreachable!(),
+                }
+            }
+
+            let events = history.iter().cloned().map(Event::new).collect::<Vec<_>>();
+            let replayed = crate::Ledger::new(id, &events).unwrap();
+            let opened = history
+                .iter()
+                .filter_map(|event| match event {
+                    Event::AccountOpened { id, .. } => Some(*id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for account in &opened {
+                prop_assert_eq!(replayed.balance(*account), ledger.balance(*account));
+            }
+            let total = opened
+                .iter()
+                .map(|account| replayed.balance(*account).unwrap())
+                .sum::<i64>();
+            prop_assert_eq!(total, 0);
+        }
+    }
+}
@@ -20,6 +20,18 @@ impl Stream {
     }
 }
 
+/// Joins the identifiers with `.`, the same format [Stream] is parsed from
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.schema)?;
+        for segment in [&self.category, &self.id].into_iter().flatten() {
+            write!(f, ".{segment}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl FromStr for Stream {
     type Err = error_stack::Report<ParseError>;
 
@@ -35,21 +47,10 @@ impl FromStr for Stream {
             .collect::<Result<Vec<_>, Report<identifier::ParseError>>>() // This only gets the first Err variant
             .change_context(ParseError::InvalidStream)?;
 
-        if split.len() > 3 {
-            error_stack::bail!(ParseError::InvalidLength(split))
+        match <[Identifier; 3]>::try_from(split) {
+            Ok([schema, category, id]) => Ok(Stream::new(schema, category, id)),
+            Err(split) => error_stack::bail!(ParseError::InvalidLength(split)),
         }
-
-        let mut split = split.into_iter();
-
-        let stream = Stream {
-            schema: split
-                .next()
-                .expect("empty list, was expecting atleast one element"),
-            category: split.next(),
-            id: split.next(),
-        };
-
-        Ok(stream)
     }
 }
 
@@ -72,7 +73,7 @@ impl fmt::Display for ParseError {
             Self::InvalidStream => f.write_str("Stream id is invalid"),
             Self::InvalidLength(input) => write!(
                 f,
-                "Stream must contain atleast 3 identifiers, got {input:?}"
+                "Stream must contain exactly 3 identifiers, got {input:?}"
             ),
         }
     }
@@ -85,6 +86,7 @@ mod tests {
     use super::*;
     use crate::identifier::Identifier;
 
+    use proptest::proptest;
     use quickcheck_macros::quickcheck;
 
     #[quickcheck]
@@ -101,17 +103,45 @@ mod tests {
         stream_new == stream_parse
     }
 
-    #[test]
-    fn parse_only_schema() {
-        let stream = "chart".parse::<Stream>().ok();
+    proptest! {
+        #[test]
+        fn parse_and_display_round_trip(input in "[a-zA-Z0-9_-]{1,8}(\\.[a-zA-Z0-9_-]{1,8}){2}") {
+            let stream = input.parse::<Stream>().unwrap();
 
-        let expected = Stream {
+            assert_eq!(stream.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn display_should_skip_missing_segments() {
+        let stream = Stream {
             schema: Identifier::new("chart").unwrap(),
             category: None,
             id: None,
         };
 
-        assert_eq!(stream, Some(expected));
+        assert_eq!(stream.to_string(), "chart");
+    }
+
+    #[test]
+    fn parse_only_schema_should_report_the_identifiers() {
+        let stream = "chart".parse::<Stream>().unwrap_err();
+
+        let expected = ParseError::InvalidLength(vec![Identifier::new("chart").unwrap()]);
+
+        assert_eq!(stream.current_context(), &expected);
+    }
+
+    #[test]
+    fn parse_schema_and_category_should_report_the_identifiers() {
+        let stream = "chart.ledger".parse::<Stream>().unwrap_err();
+
+        let expected = ParseError::InvalidLength(vec![
+            Identifier::new("chart").unwrap(),
+            Identifier::new("ledger").unwrap(),
+        ]);
+
+        assert_eq!(stream.current_context(), &expected);
     }
 
     #[test]
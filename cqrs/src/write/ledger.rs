@@ -1,24 +1,31 @@
-use chrono::prelude::*;
+use chrono::{prelude::*, Duration};
 use std::{
-    collections::HashSet,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    num::NonZeroUsize,
     ops::{Deref, Not},
-    sync::Arc,
 };
 
 use personal_finance::{
     account::{Category, Name, Number},
-    balance::Balance,
+    balance::{Balance, NetBalance},
+    entry::ValidatedJournal,
+    error::AmountError,
 };
 
 use crate::{
-    error::{AccountError, LedgerError, TransactionError},
-    events::{EventPointer, EventPointerType},
-    Event,
+    currency::{Currency, ExchangeRate},
+    error::{AccountError, BuildError, LedgerError, TransactionError},
+    events::{for_ledger, EventPointer, EventPointerType},
+    write::Aggregate,
+    Event, Posting, Postings,
 };
 
 /// A ledger id is a string starting with any alphanumeric character [a-zA-Z0-9]
 /// followed by any valid character in [a-zA-Z0-9_-]
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
 pub struct LedgerId(String);
 
 impl LedgerId {
@@ -32,6 +39,30 @@ impl LedgerId {
                     .then_some(LedgerId(id.to_owned()))
             })
     }
+
+    /// Create a [LedgerId] from user input, ignoring surrounding whitespace
+    pub fn new_trimmed(id: &str) -> Option<Self> {
+        Self::new(id.trim())
+    }
+
+    /// Parse user input the same as [LedgerId::new_trimmed] reporting invalid input
+    pub fn parse(id: &str) -> Result<Self, BuildError> {
+        Self::new_trimmed(id).ok_or_else(|| BuildError::InvalidLedger(id.to_owned()))
+    }
+}
+
+impl TryFrom<String> for LedgerId {
+    type Error = BuildError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value).ok_or(BuildError::InvalidLedger(value))
+    }
+}
+
+impl From<LedgerId> for String {
+    fn from(value: LedgerId) -> Self {
+        value.0
+    }
 }
 
 /// LedgerResolver keeps a tally on all available ledgers in the system
@@ -43,21 +74,7 @@ pub struct LedgerResolver {
 
 impl LedgerResolver {
     pub fn new(events: &[Event]) -> Self {
-        let mut ledgers = HashSet::new();
-
-        for event in events {
-            match event {
-                Event::LedgerCreated { id } => {
-                    ledgers.insert(id.clone());
-                }
-                _ => (),
-            }
-        }
-
-        Self {
-            ledgers,
-            history: events.to_vec(),
-        }
+        Self::replay(events)
     }
 
     pub fn create(&mut self, id: LedgerId) -> Result<&[Event], LedgerError> {
@@ -73,19 +90,129 @@ impl LedgerResolver {
     }
 
     pub fn get<T: AsRef<str>>(&self, id: T) -> Option<LedgerId> {
-        todo!()
+        LedgerId::new(id.as_ref()).filter(|id| self.ledgers.contains(id))
+    }
+
+    pub fn exists(&self, id: &LedgerId) -> bool {
+        self.ledgers.contains(id)
     }
 }
 
+/// Check if a ledger has been created without replaying its history
+pub fn ledger_exists(events: &[Event], id: &LedgerId) -> bool {
+    events
+        .iter()
+        .any(|event| matches!(event, Event::LedgerCreated { id: x } if x == id))
+}
+
+impl Aggregate for LedgerResolver {
+    fn apply(&mut self, event: &Event) {
+        if let Event::LedgerCreated { id } = event {
+            self.ledgers.insert(id.clone());
+        }
+
+        self.history.push(event.clone());
+    }
+}
+
+/// A transfer between two accounts in different currencies
+///
+/// `amount` in `currency`, the currency of `from`, is exchanged with `rate` into
+/// the base currency of the ledger and debited to `to`. The `from` account is
+/// credited with `book_value`, the value of the amount in the base currency when
+/// it was recorded. Any difference between the exchanged amount and the book
+/// value is posted to the `gain_loss` account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxTransfer {
+    pub from: Number,
+    pub to: Number,
+    pub amount: u32,
+    pub currency: Currency,
+    pub book_value: u32,
+    pub rate: ExchangeRate,
+    pub gain_loss: Number,
+}
+
+/// Summary of a write to a ledger, useful for reporting throughput
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOutcome {
+    pub events: usize,
+    pub ledger: LedgerId,
+}
+
+/// Metadata key of the journal notes stored by [Ledger::post_journal]
+pub const NOTES_KEY: &str = "notes";
+
+/// Number of days after today a transaction may be dated unless future dates are allowed
+pub const FUTURE_GRACE_DAYS: i64 = 7;
+
+/// Decides if transactions may be dated in the future
+///
+/// A replayed ledger allows any date so it doesn't depend on the clock. A caller
+/// posting new transactions can reject those dated more than [FUTURE_GRACE_DAYS]
+/// after today with [FuturePolicy::from_today] to catch typos like the year 2099.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FuturePolicy {
+    /// Reject transactions dated after `today` plus `grace`
+    Reject { today: Date<Utc>, grace: Duration },
+    #[default]
+    Allow,
+}
+
+impl FuturePolicy {
+    /// Reject transactions dated more than [FUTURE_GRACE_DAYS] after `today`
+    pub fn from_today(today: Date<Utc>) -> Self {
+        Self::Reject {
+            today,
+            grace: Duration::days(FUTURE_GRACE_DAYS),
+        }
+    }
+}
+
+/// Decides if an account may be both debited and credited in one transaction
+///
+/// Allowed by default, [Postings::self_offsetting] lists such accounts for a
+/// caller that only wants to warn about them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OffsetPolicy {
+    #[default]
+    Allow,
+    /// Reject the transaction with [TransactionError::SelfOffsetting]
+    Reject,
+}
+
 #[derive(Debug)]
 pub struct Ledger {
     id: LedgerId,
-    chart: HashSet<Number>,
+    chart: BTreeSet<Number>,
+    categories: BTreeMap<Number, Category>,
+    balances: BTreeMap<Number, NetBalance>,
+    sealed: Option<Date<Utc>>,
+    future_policy: FuturePolicy,
+    offset_policy: OffsetPolicy,
+    base_currency: Option<Currency>,
     history: Vec<EventPointerType>,
 }
 
 impl Ledger {
     pub fn new(id: LedgerId, events: &[EventPointerType]) -> Option<Self> {
+        Self::replay_with_progress(id, events, NonZeroUsize::MAX, |_| {})
+    }
+
+    /// Create a ledger the same as [Ledger::new] reporting the number of applied events
+    ///
+    /// `progress` is called after every `every` applied events and once more with
+    /// the final count unless that was just reported. Events before the creation
+    /// of the ledger are not counted.
+    pub fn replay_with_progress<F>(
+        id: LedgerId,
+        events: &[EventPointerType],
+        every: NonZeroUsize,
+        mut progress: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(usize),
+    {
         events.iter().position(
             |x| matches!(x.deref(), Event::LedgerCreated { id: ledger_id } if *ledger_id == id ),
         )
@@ -93,9 +220,29 @@ impl Ledger {
             let chart = Default::default();
             let history = events.to_vec();
 
-            let mut ledger = Ledger { id, chart, history };
+            let mut ledger = Ledger {
+                id,
+                chart,
+                categories: BTreeMap::new(),
+                balances: BTreeMap::new(),
+                sealed: None,
+                future_policy: FuturePolicy::default(),
+                offset_policy: OffsetPolicy::default(),
+                base_currency: None,
+                history,
+            };
+
+            let applied = &events[index..];
+            for (count, event) in (1..).zip(applied) {
+                Aggregate::apply(&mut ledger, event);
+                if count % every == 0 {
+                    progress(count);
+                }
+            }
+            if applied.len() % every != 0 {
+                progress(applied.len());
+            }
 
-            ledger.apply(&events[index..]);
             ledger
         })
     }
@@ -122,101 +269,380 @@ impl Ledger {
             .map(|issued_events| self.apply_new_events(issued_events))
     }
 
+    /// Close an open account
+    ///
+    /// Only accounts without a balance can be closed, move the remaining balance
+    /// to another account first.
     pub fn close_account(&mut self, id: Number) -> Result<&[EventPointerType], AccountError> {
+        if !self.chart.contains(&id) {
+            return Err(AccountError::NotExist);
+        }
+
+        let balance = self.balance(id).unwrap_or_default();
+        if balance != 0 {
+            return Err(AccountError::NonZeroBalance(balance));
+        }
+
+        Ok(self.apply_new_events(vec![Event::new(Event::AccountClosed {
+            ledger: self.id.clone(),
+            account: id,
+        })]))
+    }
+
+    /// The balance of an account where debits are positive and credits are negative
+    ///
+    /// Returns None if the account has never been opened or posted to in this ledger.
+    pub fn balance(&self, account: Number) -> Option<i64> {
+        self.balances.get(&account).map(|x| x.value())
+    }
+
+    /// Change the category of an account
+    ///
+    /// Reports use the latest category of an account for all of its transactions,
+    /// so recategorizing an account that already has transactions moves its
+    /// whole history, which can unbalance previously produced reports.
+    pub fn recategorize_account(
+        &mut self,
+        id: Number,
+        category: Category,
+    ) -> Result<&[EventPointerType], AccountError> {
         self.chart
             .contains(&id)
             .then(|| {
-                vec![Event::new(Event::AccountClosed {
+                vec![Event::new(Event::AccountRecategorized {
                     ledger: self.id.clone(),
                     account: id,
+                    category,
                 })]
             })
             .ok_or(AccountError::NotExist)
             .map(|issued_events| self.apply_new_events(issued_events))
     }
 
-    fn check_balance(&self, transactions: &[(Number, Balance)]) -> Result<(), TransactionError> {
-        let mut account_exists = true;
-        let mut balance_partition = (0u32, 0u32);
-        for (number, amount) in transactions.iter() {
-            account_exists = account_exists
-                .then(|| self.chart.contains(&number))
-                .unwrap_or_default();
+    fn check_balance(&self, transactions: &Postings) -> Result<(), TransactionError> {
+        let account_exists = transactions
+            .iter()
+            .all(|posting| self.chart.contains(&posting.account()));
 
-            if !account_exists {
-                break;
-            }
+        let is_single_currency = transactions.is_single_currency();
+        let is_zero_balance = transactions.is_balanced();
+        match (account_exists, is_single_currency, is_zero_balance) {
+            (false, _, _) => Err(TransactionError::AccountDoesntExist),
+            (_, false, _) => Err(TransactionError::MixedCurrencies),
+            (_, _, false) => Err(TransactionError::ImbalancedTranasactions),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_period(&self, date: &Date<Utc>) -> Result<(), TransactionError> {
+        match self.sealed {
+            Some(sealed) if *date <= sealed => Err(TransactionError::PeriodSealed),
+            _ => Ok(()),
+        }
+    }
 
-            balance_partition = match *amount {
-                Balance::Debit(x) => (
-                    balance_partition
-                        .0
-                        .checked_add(x.amount())
-                        .expect("Amount overflow"),
-                    balance_partition.1,
-                ),
-                Balance::Credit(x) => (
-                    balance_partition.0,
-                    balance_partition
-                        .1
-                        .checked_add(x.amount())
-                        .expect("Amount overflow"),
-                ),
+    fn check_future(&self, date: &Date<Utc>) -> Result<(), TransactionError> {
+        match self.future_policy {
+            FuturePolicy::Reject { today, grace } if *date > today + grace => {
+                Err(TransactionError::FutureDated)
             }
+            _ => Ok(()),
         }
+    }
 
-        let is_zero_balance = balance_partition.0 == balance_partition.1;
-        match (account_exists, is_zero_balance) {
-            (false, _) => Err(TransactionError::AccountDoesntExist),
-            (_, false) => Err(TransactionError::ImbalancedTranasactions),
+    fn check_offsetting(&self, transactions: &Postings) -> Result<(), TransactionError> {
+        match (self.offset_policy, transactions.self_offsetting().first()) {
+            (OffsetPolicy::Reject, Some(account)) => {
+                Err(TransactionError::SelfOffsetting(*account))
+            }
             _ => Ok(()),
         }
     }
 
+    pub fn set_offset_policy(&mut self, policy: OffsetPolicy) {
+        self.offset_policy = policy;
+    }
+
+    pub fn set_future_policy(&mut self, policy: FuturePolicy) {
+        self.future_policy = policy;
+    }
+
+    /// The currency of postings without a currency, needed by [Ledger::transfer_fx]
+    pub fn set_base_currency(&mut self, currency: Currency) {
+        self.base_currency = Some(currency);
+    }
+
+    /// Seal all periods up to and including `up_to`
+    ///
+    /// Transactions dated on or before a sealed date are rejected.
+    pub fn seal_period(&mut self, up_to: Date<Utc>) -> &[EventPointerType] {
+        self.apply_new_events(vec![Event::new(Event::PeriodSealed {
+            ledger: self.id.clone(),
+            up_to,
+        })])
+    }
+
     pub fn transaction<T: Into<String>>(
         &mut self,
         description: T,
-        transactions: &[(Number, Balance)],
+        transactions: Postings,
+        date: Date<Utc>,
+    ) -> Result<&[EventPointerType], TransactionError> {
+        self.transaction_with_metadata(description, transactions, date, BTreeMap::new())
+    }
+
+    /// Post a transaction tagged with free-form key/value metadata
+    pub fn transaction_with_metadata<T: Into<String>>(
+        &mut self,
+        description: T,
+        transactions: Postings,
         date: Date<Utc>,
+        metadata: BTreeMap<String, String>,
     ) -> Result<&[EventPointerType], TransactionError> {
         transactions
-            .len()
-            .gt(&0)
+            .is_empty()
+            .not()
             .then_some(())
             .ok_or(TransactionError::EmptyTransaction)
-            .and_then(|()| self.check_balance(transactions))
+            .and_then(|()| self.check_period(&date))
+            .and_then(|()| self.check_future(&date))
+            .and_then(|()| self.check_balance(&transactions))
+            .and_then(|()| self.check_offsetting(&transactions))
             .map(|_| {
                 vec![Event::new(Event::Transaction {
                     ledger: self.id.clone(),
                     description: description.into(),
                     date,
-                    transactions: transactions.to_vec(),
+                    transactions: transactions.into_inner(),
+                    metadata,
                 })]
             })
             .map(|events| self.apply_new_events(events))
     }
 
+    /// Post a validated journal as a transaction
+    ///
+    /// The notes of the journal are kept in the metadata under [NOTES_KEY], one
+    /// line per note with the account number followed by the text. A journal
+    /// without a description is posted with an empty one.
+    pub fn post_journal(
+        &mut self,
+        journal: &ValidatedJournal<'_>,
+    ) -> Result<&[EventPointerType], TransactionError> {
+        let postings = journal
+            .as_slice()
+            .iter()
+            .map(|entry| Posting::from((entry.account().number(), *entry.balance())))
+            .collect::<Vec<_>>();
+        let notes = journal
+            .notes()
+            .iter()
+            .map(|note| format!("{} {}", note.account().number(), note.text()))
+            .collect::<Vec<_>>();
+        let metadata = notes
+            .is_empty()
+            .not()
+            .then(|| (String::from(NOTES_KEY), notes.join("\n")))
+            .into_iter()
+            .collect();
+
+        self.transaction_with_metadata(
+            journal.description().cloned().unwrap_or_default(),
+            postings.into(),
+            *journal.date(),
+            metadata,
+        )
+    }
+
+    /// Transfer an amount between accounts in different currencies
+    ///
+    /// All postings are made in the base currency, a gain is credited and a loss
+    /// is debited to the gain/loss account. The rate must exchange the currency
+    /// of the transfer into the base currency of the ledger, otherwise the
+    /// transfer is rejected with [TransactionError::InvalidExchange]. A ledger
+    /// without a base currency rejects all transfers.
+    pub fn transfer_fx<T: Into<String>>(
+        &mut self,
+        description: T,
+        transfer: FxTransfer,
+        date: Date<Utc>,
+    ) -> Result<&[EventPointerType], TransactionError> {
+        let rate = transfer.rate;
+        if transfer.currency != rate.from() || self.base_currency != Some(rate.to()) {
+            return Err(TransactionError::InvalidExchange);
+        }
+
+        let exchanged = transfer
+            .rate
+            .convert(transfer.amount)
+            .ok_or(TransactionError::InvalidExchange)?;
+
+        let mut postings = Postings::new();
+        postings
+            .push(Posting::debit(transfer.to, exchanged).ok_or(TransactionError::InvalidExchange)?);
+        postings.push(
+            Posting::credit(transfer.from, transfer.book_value)
+                .ok_or(TransactionError::InvalidExchange)?,
+        );
+
+        match exchanged.cmp(&transfer.book_value) {
+            Ordering::Greater => postings.push(
+                Posting::credit(transfer.gain_loss, exchanged - transfer.book_value).unwrap(),
+            ),
+            Ordering::Less => postings
+                .push(Posting::debit(transfer.gain_loss, transfer.book_value - exchanged).unwrap()),
+            Ordering::Equal => {}
+        }
+
+        self.transaction(description, postings, date)
+    }
+
+    /// Post the opening balance of a single account against an offset account
+    ///
+    /// The offset, usually an equity account, receives the opposite side of `amount`.
+    pub fn set_opening_balance(
+        &mut self,
+        account: Number,
+        amount: Balance,
+        offset: Number,
+        date: Date<Utc>,
+    ) -> Result<&[EventPointerType], TransactionError> {
+        let opposite = match amount {
+            Balance::Debit(x) => Balance::credit(x.amount()),
+            Balance::Credit(x) => Balance::debit(x.amount()),
+        }
+        .expect("a balance is never zero");
+
+        let mut postings = Postings::new();
+        postings.push(Posting::new(account, amount));
+        postings.push(Posting::new(offset, opposite));
+
+        self.transaction("Opening balance", postings, date)
+    }
+
+    /// Move the balances of all income and expense accounts into an equity account
+    ///
+    /// This posts the closing entries at the end of a period, leaving every
+    /// income and expense account at zero. Returns [TransactionError::EmptyTransaction]
+    /// if there is no balance to close.
+    pub fn close_income_and_expenses(
+        &mut self,
+        equity: Number,
+        date: Date<Utc>,
+    ) -> Result<&[EventPointerType], TransactionError> {
+        fn reversed(net: i64) -> Result<Balance, AmountError> {
+            if net > 0 {
+                Balance::try_credit(net.unsigned_abs())
+            } else {
+                Balance::try_debit(net.unsigned_abs())
+            }
+        }
+
+        let closing = self
+            .balances
+            .iter()
+            .filter(|(account, _)| {
+                matches!(
+                    self.categories.get(account),
+                    Some(Category::Income | Category::Expenses)
+                )
+            })
+            .map(|(account, net)| (*account, net.value()))
+            .filter(|(_, net)| *net != 0)
+            .collect::<Vec<_>>();
+
+        let mut postings = Postings::new();
+        for (account, net) in &closing {
+            postings.push(Posting::new(*account, reversed(*net)?));
+        }
+
+        let total = closing.iter().map(|(_, net)| net).sum::<i64>();
+        if total != 0 {
+            postings.push(Posting::new(equity, reversed(-total)?));
+        }
+
+        self.transaction("Closing entries", postings, date)
+    }
+
+    /// Numbers of all open accounts in ascending order
+    pub fn open_accounts(&self) -> Vec<Number> {
+        self.chart.iter().copied().collect()
+    }
+
+    /// All transaction lines posted to an account with their date and description
+    pub fn account_lines(
+        &self,
+        account: Number,
+    ) -> Result<Vec<(Date<Utc>, Balance, String)>, AccountError> {
+        if !self.chart.contains(&account) {
+            return Err(AccountError::NotExist);
+        }
+
+        Ok(for_ledger(self.history.iter().map(Deref::deref), &self.id)
+            .filter_map(|event| match event {
+                Event::Transaction {
+                    description,
+                    date,
+                    transactions,
+                    ..
+                } => Some((description, date, transactions)),
+                _ => None,
+            })
+            .flat_map(|(description, date, transactions)| {
+                transactions
+                    .iter()
+                    .filter(move |posting| posting.account() == account)
+                    .map(move |posting| (*date, *posting.amount(), description.clone()))
+            })
+            .collect())
+    }
+
     fn apply_new_events(&mut self, events: Vec<EventPointerType>) -> &[EventPointerType] {
         let number_of_new_events = events.len();
-        self.apply(&events);
+        self.apply_all(&events);
         self.history.extend(events);
 
         let index = self.history.len().saturating_sub(number_of_new_events);
         &self.history[index..]
     }
 
-    fn apply(&mut self, events: &[EventPointerType]) {
+    fn apply_all(&mut self, events: &[EventPointerType]) {
         for event in events {
-            match event.deref() {
-                Event::AccountOpened { ledger, id, .. } if *ledger == self.id => {
-                    self.chart.insert(*id);
-                }
-                Event::AccountClosed { ledger, account } if *ledger == self.id => {
-                    self.chart.remove(account);
+            Aggregate::apply(self, event);
+        }
+    }
+}
+
+/// A ledger can't be replayed from an empty state since it needs an id, see [Ledger::new]
+impl Aggregate for Ledger {
+    fn apply(&mut self, event: &Event) {
+        if event.ledger() != Some(&self.id) {
+            return;
+        }
+
+        match event {
+            Event::AccountOpened { id, category, .. } => {
+                self.chart.insert(*id);
+                self.categories.insert(*id, *category);
+                self.balances.entry(*id).or_default();
+            }
+            Event::AccountRecategorized {
+                account, category, ..
+            } => {
+                self.categories.insert(*account, *category);
+            }
+            Event::AccountClosed { account, .. } => {
+                self.chart.remove(account);
+            }
+            Event::Transaction { transactions, .. } => {
+                for posting in transactions {
+                    *self.balances.entry(posting.account()).or_default() += *posting.amount();
                 }
-                Event::Transaction { ledger, .. } if *ledger == self.id => {}
-                _ => {}
             }
+            Event::PeriodSealed { up_to, .. } => {
+                self.sealed = self.sealed.max(Some(*up_to));
+            }
+            _ => {}
         }
     }
 }
@@ -227,6 +653,56 @@ mod tests {
 
     use proptest::proptest;
 
+    use std::num::NonZeroU32;
+
+    use test_case::test_case;
+
+    fn ledger_with_accounts(id: &str) -> Ledger {
+        let id = LedgerId::new(id).unwrap();
+        let mut ledger = Ledger::new(
+            id.clone(),
+            &[Event::new(Event::LedgerCreated { id: id.clone() })],
+        )
+        .unwrap();
+
+        ledger
+            .open_account(
+                Number::new(101).unwrap(),
+                Name::new("Bank account").unwrap(),
+                Category::Asset,
+            )
+            .unwrap();
+        ledger
+            .open_account(
+                Number::new(501).unwrap(),
+                Name::new("Groceries").unwrap(),
+                Category::Expenses,
+            )
+            .unwrap();
+
+        ledger
+    }
+
+    fn grocery_shopping() -> Postings {
+        vec![
+            Posting::credit(Number::new(101).unwrap(), 150).unwrap(),
+            Posting::debit(Number::new(501).unwrap(), 150).unwrap(),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn transaction_in_sealed_period_should_be_rejected() {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger.seal_period(Utc.ymd(2014, 3, 31));
+
+        let february = ledger.transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 2, 14));
+        assert_eq!(february, Err(TransactionError::PeriodSealed));
+
+        let april = ledger.transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 4, 1));
+        assert!(april.is_ok());
+    }
+
     proptest! {
         #[test]
         fn invalid_ledger_ids(s in "[_-][a-zA-Z0-9]*") {
@@ -240,4 +716,530 @@ mod tests {
             assert_eq!(LedgerId::new(&s), Some(LedgerId(s)))
         }
     }
+
+    #[test]
+    fn transaction_with_mixed_currencies_should_be_rejected() {
+        let mut ledger = ledger_with_accounts("2014");
+        let postings = Postings::from(vec![
+            Posting::debit(Number::new(501).unwrap(), 150)
+                .unwrap()
+                .with_currency(Currency::new("USD").unwrap()),
+            Posting::credit(Number::new(101).unwrap(), 150)
+                .unwrap()
+                .with_currency(Currency::new("EUR").unwrap()),
+        ]);
+
+        let actual = ledger.transaction("Groceries", postings, Utc.ymd(2014, 4, 1));
+
+        assert_eq!(actual, Err(TransactionError::MixedCurrencies));
+    }
+
+    #[test_case(1045 => vec![
+        Posting::debit(Number::new(102).unwrap(), 1045).unwrap(),
+        Posting::credit(Number::new(103).unwrap(), 1000).unwrap(),
+        Posting::credit(Number::new(801).unwrap(), 45).unwrap(),
+    ] ; "gain")]
+    #[test_case(980 => vec![
+        Posting::debit(Number::new(102).unwrap(), 980).unwrap(),
+        Posting::credit(Number::new(103).unwrap(), 1000).unwrap(),
+        Posting::debit(Number::new(801).unwrap(), 20).unwrap(),
+    ] ; "loss")]
+    #[test_case(1000 => vec![
+        Posting::debit(Number::new(102).unwrap(), 1000).unwrap(),
+        Posting::credit(Number::new(103).unwrap(), 1000).unwrap(),
+    ] ; "no difference")]
+    fn transfer_fx_posts_difference_to_gain_loss_account(rate: u32) -> Vec<Posting> {
+        let mut ledger = ledger_with_fx_accounts();
+
+        let events = ledger
+            .transfer_fx("Exchange", usd_to_sek(rate), Utc.ymd(2014, 4, 1))
+            .unwrap();
+
+        match events[0].deref() {
+            Event::Transaction { transactions, .. } => {
+                assert!(Postings::from(transactions.clone()).is_balanced());
+                transactions.clone()
+            }
+            _ => panic!("expected a transaction"),
+        }
+    }
+
+    fn ledger_with_fx_accounts() -> Ledger {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger.set_base_currency(Currency::new("SEK").unwrap());
+        for (number, name) in [(102, "Bank SEK"), (103, "Bank USD"), (801, "FX gain/loss")] {
+            ledger
+                .open_account(
+                    Number::new(number).unwrap(),
+                    Name::new(name).unwrap(),
+                    Category::Asset,
+                )
+                .unwrap();
+        }
+
+        ledger
+    }
+
+    fn usd_to_sek(rate: u32) -> FxTransfer {
+        FxTransfer {
+            from: Number::new(103).unwrap(),
+            to: Number::new(102).unwrap(),
+            amount: 100,
+            currency: Currency::new("USD").unwrap(),
+            book_value: 1000,
+            rate: ExchangeRate::new(
+                Currency::new("USD").unwrap(),
+                Currency::new("SEK").unwrap(),
+                rate,
+                NonZeroU32::new(100).unwrap(),
+            ),
+            gain_loss: Number::new(801).unwrap(),
+        }
+    }
+
+    #[test_case(Some("SEK"), "EUR", "USD", "SEK" ; "amount not in the rate currency")]
+    #[test_case(Some("SEK"), "USD", "USD", "EUR" ; "rate not into the base currency")]
+    #[test_case(None, "USD", "USD", "SEK" ; "ledger without a base currency")]
+    fn transfer_fx_with_mismatched_currencies_should_be_rejected(
+        base: Option<&str>,
+        currency: &str,
+        from: &str,
+        to: &str,
+    ) {
+        let mut ledger = ledger_with_fx_accounts();
+        ledger.base_currency = base.map(|x| Currency::new(x).unwrap());
+        let transfer = FxTransfer {
+            currency: Currency::new(currency).unwrap(),
+            rate: ExchangeRate::new(
+                Currency::new(from).unwrap(),
+                Currency::new(to).unwrap(),
+                1000,
+                NonZeroU32::new(100).unwrap(),
+            ),
+            ..usd_to_sek(1000)
+        };
+
+        let actual = ledger.transfer_fx("Exchange", transfer, Utc.ymd(2014, 4, 1));
+
+        assert_eq!(actual, Err(TransactionError::InvalidExchange));
+    }
+
+    #[test]
+    fn open_accounts_should_be_in_ascending_order() {
+        let mut ledger = ledger_with_accounts("2014");
+        for number in [301, 102, 999] {
+            ledger
+                .open_account(
+                    Number::new(number).unwrap(),
+                    Name::new("Account").unwrap(),
+                    Category::Asset,
+                )
+                .unwrap();
+        }
+        ledger.close_account(Number::new(999).unwrap()).unwrap();
+
+        let actual = ledger
+            .open_accounts()
+            .into_iter()
+            .map(|x| x.number())
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, vec![101, 102, 301, 501]);
+    }
+
+    #[test]
+    fn set_opening_balance_should_offset_against_equity() {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger
+            .open_account(
+                Number::new(301).unwrap(),
+                Name::new("Opening equity").unwrap(),
+                Category::Equity,
+            )
+            .unwrap();
+
+        let events = ledger
+            .set_opening_balance(
+                Number::new(101).unwrap(),
+                Balance::debit(1000).unwrap(),
+                Number::new(301).unwrap(),
+                Utc.ymd(2014, 1, 1),
+            )
+            .unwrap();
+        assert_eq!(events.len(), 1);
+
+        assert_eq!(
+            ledger.account_lines(Number::new(101).unwrap()),
+            Ok(vec![(
+                Utc.ymd(2014, 1, 1),
+                Balance::debit(1000).unwrap(),
+                String::from("Opening balance")
+            )])
+        );
+        assert_eq!(
+            ledger.account_lines(Number::new(301).unwrap()),
+            Ok(vec![(
+                Utc.ymd(2014, 1, 1),
+                Balance::credit(1000).unwrap(),
+                String::from("Opening balance")
+            )])
+        );
+    }
+
+    #[test]
+    fn set_opening_balance_with_unknown_offset_should_be_an_error() {
+        let mut ledger = ledger_with_accounts("2014");
+
+        let actual = ledger.set_opening_balance(
+            Number::new(101).unwrap(),
+            Balance::debit(1000).unwrap(),
+            Number::new(301).unwrap(),
+            Utc.ymd(2014, 1, 1),
+        );
+
+        assert_eq!(actual, Err(TransactionError::AccountDoesntExist));
+    }
+
+    fn self_offsetting_postings() -> Postings {
+        vec![
+            Posting::debit(Number::new(101).unwrap(), 100).unwrap(),
+            Posting::credit(Number::new(101).unwrap(), 40).unwrap(),
+            Posting::credit(Number::new(501).unwrap(), 60).unwrap(),
+        ]
+        .into()
+    }
+
+    #[test_case(OffsetPolicy::Allow => Ok(1) ; "permissive by default")]
+    #[test_case(OffsetPolicy::Reject => Err(TransactionError::SelfOffsetting(Number::new(101).unwrap())) ; "strict")]
+    fn self_offsetting_transaction(policy: OffsetPolicy) -> Result<usize, TransactionError> {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger.set_offset_policy(policy);
+
+        ledger
+            .transaction("Refund", self_offsetting_postings(), Utc.ymd(2014, 4, 1))
+            .map(<[_]>::len)
+    }
+
+    #[test_case(2 => vec![2, 4, 5] ; "final count reported")]
+    #[test_case(5 => vec![5] ; "final count not repeated")]
+    fn replay_with_progress_reports_applied_events(every: usize) -> Vec<usize> {
+        let id = LedgerId::new("2014").unwrap();
+        let mut events = vec![
+            Event::new(Event::LedgerCreated {
+                id: LedgerId::new("other").unwrap(),
+            }),
+            Event::new(Event::LedgerCreated { id: id.clone() }),
+        ];
+        events.extend((101..105).map(|number| {
+            Event::new(
+                Event::account_opened(id.clone(), number, "Account", Category::Asset).unwrap(),
+            )
+        }));
+
+        let mut reported = Vec::new();
+        let ledger =
+            Ledger::replay_with_progress(id, &events, NonZeroUsize::new(every).unwrap(), |count| {
+                reported.push(count)
+            })
+            .unwrap();
+
+        assert_eq!(ledger.open_accounts().len(), 4);
+        reported
+    }
+
+    #[test]
+    fn recategorize_unknown_account_should_be_an_error() {
+        let mut ledger = ledger_with_accounts("2014");
+
+        let actual = ledger.recategorize_account(Number::new(999).unwrap(), Category::Equity);
+
+        assert_eq!(actual, Err(AccountError::NotExist));
+    }
+
+    #[test]
+    fn account_lines_should_only_contain_postings_to_the_account() {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger
+            .transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 4, 1))
+            .unwrap();
+
+        let actual = ledger.account_lines(Number::new(501).unwrap());
+
+        assert_eq!(
+            actual,
+            Ok(vec![(
+                Utc.ymd(2014, 4, 1),
+                Balance::debit(150).unwrap(),
+                String::from("Groceries")
+            )])
+        );
+        assert_eq!(
+            ledger.account_lines(Number::new(999).unwrap()),
+            Err(AccountError::NotExist)
+        );
+    }
+
+    #[test_case(" 2014-q2 " => LedgerId::new("2014-q2") ; "padded with spaces")]
+    #[test_case("\t2014-q2\n" => LedgerId::new("2014-q2") ; "padded with tabs and newlines")]
+    #[test_case("2014 q2" => None ; "inner whitespace")]
+    #[test_case("   " => None ; "only whitespace")]
+    fn ledger_id_new_trimmed(id: &str) -> Option<LedgerId> {
+        LedgerId::new_trimmed(id)
+    }
+
+    #[test_case("2014" => true)]
+    #[test_case("2015" => false)]
+    fn ledger_exists_only_for_created_ledgers(id: &str) -> bool {
+        let events = [Event::LedgerCreated {
+            id: LedgerId::new("2014").unwrap(),
+        }];
+        let id = LedgerId::new(id).unwrap();
+
+        let resolver = LedgerResolver::new(&events);
+        assert_eq!(resolver.exists(&id), ledger_exists(&events, &id));
+
+        resolver.exists(&id)
+    }
+
+    #[test_case(FuturePolicy::from_today(Utc.ymd(2014, 4, 1)), Utc.ymd(2099, 4, 1) => Err(TransactionError::FutureDated) ; "far future is rejected")]
+    #[test_case(FuturePolicy::from_today(Utc.ymd(2014, 4, 1)), Utc.ymd(2014, 4, 3) => Ok(()) ; "within grace is allowed")]
+    #[test_case(FuturePolicy::Allow, Utc.ymd(2099, 4, 1) => Ok(()) ; "future explicitly allowed")]
+    fn future_dated_transaction(
+        policy: FuturePolicy,
+        date: Date<Utc>,
+    ) -> Result<(), TransactionError> {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger.set_future_policy(policy);
+
+        ledger
+            .transaction("Groceries", grocery_shopping(), date)
+            .map(|_| ())
+    }
+
+    #[test]
+    fn journal_notes_should_survive_posting() {
+        let bank = personal_finance::entry::Account::new(
+            Number::new(101).unwrap(),
+            Name::new("Bank account").unwrap(),
+            Category::Asset,
+        );
+        let groceries = personal_finance::entry::Account::new(
+            Number::new(501).unwrap(),
+            Name::new("Groceries").unwrap(),
+            Category::Expenses,
+        );
+        let mut journal = personal_finance::entry::Journal::new(Utc.ymd(2014, 4, 1));
+        journal.set_description("Groceries");
+        journal.push(&groceries, Balance::debit(100).unwrap());
+        journal.push_note(&bank, "Reimbursed next month");
+        journal.push(&bank, Balance::credit(100).unwrap());
+        journal.push_note(&groceries, "Weekly shopping");
+        let mut ledger = ledger_with_accounts("2014");
+
+        let events = ledger.post_journal(&journal.validate().unwrap()).unwrap();
+
+        assert!(matches!(
+            events[0].deref(),
+            Event::Transaction { metadata, .. }
+                if metadata[NOTES_KEY] == "101 Reimbursed next month\n501 Weekly shopping"
+        ));
+    }
+
+    fn replay_stream() -> Vec<Event> {
+        let id = LedgerId::new("2014").unwrap();
+        vec![
+            Event::LedgerCreated { id: id.clone() },
+            Event::AccountOpened {
+                ledger: id.clone(),
+                id: Number::new(101).unwrap(),
+                name: Name::new("Bank account").unwrap(),
+                category: Category::Asset,
+            },
+            Event::LedgerCreated {
+                id: LedgerId::new("2015").unwrap(),
+            },
+            Event::AccountClosed {
+                ledger: id,
+                account: Number::new(101).unwrap(),
+            },
+        ]
+    }
+
+    #[test]
+    fn replay_ledger_resolver_through_aggregate() {
+        let events = replay_stream();
+
+        let resolver = LedgerResolver::replay(&events);
+
+        assert_eq!(resolver, LedgerResolver::new(&events));
+        assert!(resolver.exists(&LedgerId::new("2014").unwrap()));
+        assert!(resolver.exists(&LedgerId::new("2015").unwrap()));
+    }
+
+    #[test]
+    fn apply_ledger_through_aggregate() {
+        let events = replay_stream();
+        let id = LedgerId::new("2014").unwrap();
+        let mut ledger = Ledger::new(id, &[Event::new(events[0].clone())]).unwrap();
+
+        Aggregate::apply(&mut ledger, &events[1]);
+        assert!(ledger.chart.contains(&Number::new(101).unwrap()));
+
+        for event in &events[2..] {
+            Aggregate::apply(&mut ledger, event);
+        }
+        assert!(ledger.chart.is_empty());
+    }
+
+    #[test]
+    fn open_accounts_should_ignore_other_ledgers_in_shared_history() {
+        let first = LedgerId::new("2014").unwrap();
+        let second = LedgerId::new("2015").unwrap();
+        let mut history = vec![
+            Event::new(Event::LedgerCreated { id: first.clone() }),
+            Event::new(Event::LedgerCreated { id: second.clone() }),
+        ];
+
+        let mut ledger = Ledger::new(first.clone(), &history).unwrap();
+        let opened = ledger
+            .open_account(
+                Number::new(101).unwrap(),
+                Name::new("Bank").unwrap(),
+                Category::Asset,
+            )
+            .unwrap()
+            .to_vec();
+        assert!(matches!(
+            opened[0].deref(),
+            Event::AccountOpened { ledger, .. } if *ledger == first
+        ));
+        history.extend(opened);
+
+        let mut ledger = Ledger::new(second.clone(), &history).unwrap();
+        let opened = ledger
+            .open_account(
+                Number::new(201).unwrap(),
+                Name::new("Loan").unwrap(),
+                Category::Liability,
+            )
+            .unwrap()
+            .to_vec();
+        history.extend(opened);
+
+        let first_accounts = Ledger::new(first, &history).unwrap().open_accounts();
+        let second_accounts = Ledger::new(second, &history).unwrap().open_accounts();
+
+        assert_eq!(first_accounts, vec![Number::new(101).unwrap()]);
+        assert_eq!(second_accounts, vec![Number::new(201).unwrap()]);
+    }
+
+    #[test]
+    fn closing_account_with_balance_should_fail_until_balanced() {
+        let mut ledger = ledger_with_accounts("2014");
+        let groceries = Number::new(501).unwrap();
+        ledger
+            .transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 2, 14))
+            .unwrap();
+
+        assert_eq!(
+            ledger.close_account(groceries),
+            Err(AccountError::NonZeroBalance(150))
+        );
+
+        let refund = vec![
+            Posting::debit(Number::new(101).unwrap(), 150).unwrap(),
+            Posting::credit(groceries, 150).unwrap(),
+        ];
+        ledger
+            .transaction("Refund", refund.into(), Utc.ymd(2014, 2, 15))
+            .unwrap();
+
+        assert_eq!(ledger.balance(groceries), Some(0));
+        assert!(ledger.close_account(groceries).is_ok());
+    }
+
+    #[test]
+    fn balances_should_follow_postings() {
+        let mut ledger = ledger_with_accounts("2014");
+        let bank = Number::new(101).unwrap();
+        let groceries = Number::new(501).unwrap();
+
+        assert_eq!(ledger.balance(bank), Some(0));
+
+        ledger
+            .transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 2, 14))
+            .unwrap();
+        let split = vec![
+            Posting::credit(bank, 80).unwrap(),
+            Posting::debit(groceries, 50).unwrap(),
+            Posting::debit(groceries, 30).unwrap(),
+        ];
+        ledger
+            .transaction("Groceries", split.into(), Utc.ymd(2014, 2, 20))
+            .unwrap();
+
+        assert_eq!(ledger.balance(bank), Some(-230));
+        assert_eq!(ledger.balance(groceries), Some(230));
+        assert_eq!(ledger.balance(Number::new(999).unwrap()), None);
+    }
+
+    #[test]
+    fn closing_income_and_expenses_should_leave_zero_net_income() {
+        let mut ledger = ledger_with_accounts("2014");
+        for (number, name, category) in [
+            (301, "Retained earnings", Category::Equity),
+            (401, "Salary", Category::Income),
+        ] {
+            ledger
+                .open_account(
+                    Number::new(number).unwrap(),
+                    Name::new(name).unwrap(),
+                    category,
+                )
+                .unwrap();
+        }
+        let salary = vec![
+            Posting::debit(Number::new(101).unwrap(), 1000).unwrap(),
+            Posting::credit(Number::new(401).unwrap(), 1000).unwrap(),
+        ];
+        ledger
+            .transaction("Salary", salary.into(), Utc.ymd(2014, 1, 25))
+            .unwrap();
+        ledger
+            .transaction("Groceries", grocery_shopping(), Utc.ymd(2014, 2, 14))
+            .unwrap();
+
+        ledger
+            .close_income_and_expenses(Number::new(301).unwrap(), Utc.ymd(2014, 12, 31))
+            .unwrap();
+
+        let statement = crate::projections::income_statement(
+            ledger.history.iter().map(Deref::deref),
+            &LedgerId::new("2014").unwrap(),
+            Utc.ymd(2014, 1, 1),
+            Utc.ymd(2014, 12, 31),
+        );
+        assert_eq!(statement.net(), 0);
+        assert_eq!(ledger.balance(Number::new(401).unwrap()), Some(0));
+        assert_eq!(ledger.balance(Number::new(501).unwrap()), Some(0));
+        assert_eq!(ledger.balance(Number::new(301).unwrap()), Some(-850));
+        assert_eq!(ledger.balance(Number::new(101).unwrap()), Some(850));
+    }
+
+    #[test]
+    fn closing_without_income_or_expenses_should_be_empty() {
+        let mut ledger = ledger_with_accounts("2014");
+        ledger
+            .open_account(
+                Number::new(301).unwrap(),
+                Name::new("Retained earnings").unwrap(),
+                Category::Equity,
+            )
+            .unwrap();
+
+        let actual =
+            ledger.close_income_and_expenses(Number::new(301).unwrap(), Utc.ymd(2014, 12, 31));
+
+        assert_eq!(actual, Err(TransactionError::EmptyTransaction));
+    }
 }
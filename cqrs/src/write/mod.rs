@@ -1 +1,24 @@
+use crate::Event;
+
 pub mod ledger;
+
+/// A write model that is rebuilt by applying events in order
+pub trait Aggregate {
+    /// Update the aggregate with a single event, events that don't concern
+    /// the aggregate are ignored
+    fn apply(&mut self, event: &Event);
+
+    /// Build the aggregate from an empty state by applying all events
+    fn replay<'a, I>(events: I) -> Self
+    where
+        Self: Default,
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let mut aggregate = Self::default();
+        for event in events {
+            aggregate.apply(event);
+        }
+
+        aggregate
+    }
+}
@@ -1,11 +1,12 @@
-use std::{fmt, str::FromStr};
+use std::{fmt, num::NonZeroU32, ops::RangeInclusive, str::FromStr};
 
 use enum_iterator::IntoEnumIterator;
 
-use crate::balance::Balance;
+use crate::balance::{Balance, Transaction};
 
 /// These are the different types of an Account can be associated with.
-#[derive(Debug, Clone, Copy, IntoEnumIterator, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, IntoEnumIterator, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Category {
     Asset,
     Liability,
@@ -31,14 +32,38 @@ impl Category {
         CreditIter::new()
     }
 
+    /// The conventional range of account numbers for this category
+    ///
+    /// Asset accounts are numbered 101-199, Liability 201-299, Equity 301-399,
+    /// Income 401-499 and Expenses 501-599.
+    pub fn number_range(&self) -> RangeInclusive<u32> {
+        let base = match self {
+            Category::Asset => 100,
+            Category::Liability => 200,
+            Category::Equity => 300,
+            Category::Income => 400,
+            Category::Expenses => 500,
+        };
+
+        base + 1..=base + 99
+    }
+
     /// Create a transaction that increases this type of Category
+    ///
+    /// Returns None if `amount` is zero, see [Category::increase_nonzero].
     pub fn increase(&self, amount: u32) -> Option<Balance> {
+        NonZeroU32::new(amount).map(|amount| self.increase_nonzero(amount))
+    }
+
+    /// Create a transaction that increases this type of Category
+    pub fn increase_nonzero(&self, amount: NonZeroU32) -> Balance {
         match self {
-            Category::Asset => Balance::debit(amount),
-            Category::Liability => Balance::credit(amount),
-            Category::Equity => Balance::credit(amount),
-            Category::Income => Balance::credit(amount),
-            Category::Expenses => Balance::debit(amount),
+            Category::Asset | Category::Expenses => {
+                Balance::Debit(Transaction::debit_unchecked(amount.get()))
+            }
+            Category::Liability | Category::Equity | Category::Income => {
+                Balance::Credit(Transaction::credit_unchecked(amount.get()))
+            }
         }
     }
 }
@@ -55,21 +80,30 @@ impl fmt::Display for Category {
     }
 }
 
+/// Parse a category ignoring case, both singular and plural forms are accepted.
 impl FromStr for Category {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.trim() {
-            "Asset" => Ok(Self::Asset),
-            "Liability" => Ok(Self::Liability),
-            "Equity" => Ok(Self::Equity),
-            "Income" => Ok(Self::Income),
-            "Expenses" => Ok(Self::Expenses),
+        match s.trim().to_lowercase().as_str() {
+            "asset" | "assets" => Ok(Self::Asset),
+            "liability" | "liabilities" => Ok(Self::Liability),
+            "equity" | "equities" => Ok(Self::Equity),
+            "income" | "incomes" => Ok(Self::Income),
+            "expense" | "expenses" => Ok(Self::Expenses),
             _ => Err(ParseError),
         }
     }
 }
 
+impl TryFrom<&str> for Category {
+    type Error = ParseError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ParseError;
 
@@ -131,6 +165,7 @@ mod tests {
 
     use quickcheck::{Arbitrary, Gen};
     use quickcheck_macros::quickcheck;
+    use test_case::test_case;
 
     #[derive(Debug, Clone, Copy)]
     struct DebitCategory(Category);
@@ -188,8 +223,31 @@ mod tests {
         inc == Balance::credit(amount)
     }
 
+    #[quickcheck]
+    fn account_category_increase_nonzero_should_equal_increase(amount: NonZeroU32) -> bool {
+        Category::into_enum_iter().all(|category| {
+            category.increase(amount.get()) == Some(category.increase_nonzero(amount))
+        })
+    }
+
+    #[test]
+    fn account_category_increase_zero_should_be_none() {
+        assert!(Category::into_enum_iter().all(|category| category.increase(0).is_none()));
+    }
+
     #[quickcheck]
     fn category_to_string_then_parse_should_be_original(category: Category) -> bool {
         category == category.to_string().parse().unwrap()
     }
+
+    #[test_case("Asset" => Some(Category::Asset))]
+    #[test_case("assets" => Some(Category::Asset))]
+    #[test_case("ASSET" => Some(Category::Asset) ; "upper case asset")]
+    #[test_case("liabilities" => Some(Category::Liability))]
+    #[test_case("Expenses" => Some(Category::Expenses))]
+    #[test_case("expense" => Some(Category::Expenses))]
+    #[test_case("foo" => None)]
+    fn category_try_from(input: &str) -> Option<Category> {
+        Category::try_from(input).ok()
+    }
 }
@@ -1,4 +1,6 @@
-use std::{fmt, num::NonZeroU32};
+use std::{fmt, num::NonZeroU32, ops::RangeInclusive, str::FromStr};
+
+use crate::error::NameError;
 
 mod category;
 
@@ -6,6 +8,8 @@ pub use category::Category;
 
 /// An account number to identify an account.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Number(NonZeroU32);
 
 impl Number {
@@ -14,11 +18,86 @@ impl Number {
         NonZeroU32::new(value).map(Self)
     }
 
+    /// Create a new [Number] only if `value` is positive and within `range`
+    ///
+    /// # Examples
+    /// ```
+    /// use personal_finance::account::Number;
+    ///
+    /// assert!(Number::new_in_range(101, 1..=9999).is_some());
+    /// assert!(Number::new_in_range(10000, 1..=9999).is_none());
+    /// ```
+    pub fn new_in_range(value: u32, range: RangeInclusive<u32>) -> Option<Self> {
+        range.contains(&value).then_some(value).and_then(Self::new)
+    }
+
     pub fn number(&self) -> u32 {
         self.0.get()
     }
+
+    /// Zero-pad the number to at least `width` digits for tabular output
+    ///
+    /// A number wider than `width` is not truncated.
+    pub fn formatted(&self, width: usize) -> String {
+        format!("{:0width$}", self.0.get(), width = width)
+    }
+
+    /// Parse a [Number] from a string as found in imported charts
+    ///
+    /// Surrounding whitespace is trimmed and separators (`.`, `,`, `_` and spaces)
+    /// are stripped, so `"1000.10"` becomes `100010`. Leading zeros are allowed.
+    ///
+    /// # Examples
+    /// ```
+    /// use personal_finance::account::Number;
+    ///
+    /// assert_eq!(Number::parse("0101").unwrap().number(), 101);
+    /// assert_eq!(Number::parse("1000.10").unwrap().number(), 100010);
+    /// assert!(Number::parse("0").is_err());
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, ParseNumberError> {
+        let digits = s
+            .trim()
+            .chars()
+            .filter(|ch| !matches!(ch, '.' | ',' | '_' | ' '))
+            .collect::<String>();
+
+        let value = digits
+            .parse::<u32>()
+            .map_err(|_| ParseNumberError::Invalid)?;
+
+        Self::new(value).ok_or(ParseNumberError::Zero)
+    }
+}
+
+impl FromStr for Number {
+    type Err = ParseNumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Error returned when parsing a [Number] from a string fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseNumberError {
+    /// The string does not contain a valid number
+    Invalid,
+    /// The number is zero which is not a valid account number
+    Zero,
 }
 
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => f.write_str("Unable to parse account number"),
+            Self::Zero => f.write_str("Account number must not be zero"),
+        }
+    }
+}
+
+impl std::error::Error for ParseNumberError {}
+
 impl From<Number> for u32 {
     fn from(number: Number) -> Self {
         number.0.get()
@@ -45,7 +124,8 @@ impl fmt::Display for Number {
     }
 }
 
-/// An account name is a trimmed non-empty string.
+/// An account name is a trimmed non-empty string of at most [Name::MAX_LENGTH]
+/// characters without control characters.
 ///
 /// # Examples
 /// ```
@@ -57,20 +137,29 @@ impl fmt::Display for Number {
 /// let name = account::Name::new("    ");
 /// assert_eq!(name, None);
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
 pub struct Name(String);
 
 impl Name {
+    /// The maximum number of characters in a name
+    pub const MAX_LENGTH: usize = 100;
+
     /// Create a new AccountName
     ///
     /// This trims and returns Some([Name]) if it is not an empty string,
-    /// otherwise it return None.
+    /// not longer than [Name::MAX_LENGTH] characters and has no control
+    /// characters, otherwise it return None.
     pub fn new<T: AsRef<str>>(name: T) -> Option<Self> {
-        let name = name.as_ref().trim().to_owned();
-        if name.is_empty() {
+        let name = name.as_ref().trim();
+        if name.is_empty()
+            || name.chars().count() > Self::MAX_LENGTH
+            || name.chars().any(char::is_control)
+        {
             None
         } else {
-            Some(Name(name))
+            Some(Name(name.to_owned()))
         }
     }
 
@@ -108,6 +197,14 @@ impl PartialOrd<&str> for Name {
     }
 }
 
+impl TryFrom<String> for Name {
+    type Error = NameError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value).ok_or(NameError(value))
+    }
+}
+
 impl From<Name> for String {
     fn from(other: Name) -> Self {
         other.0
@@ -126,12 +223,53 @@ mod tests {
 
     use test_case::test_case;
 
+    #[test_case(0, 0..=9999 => None ; "zero")]
+    #[test_case(10000, 1..=9999 => None ; "above range")]
+    #[test_case(99, 100..=999 => None ; "below range")]
+    #[test_case(1, 1..=9999 => Some(1) ; "lower bound")]
+    #[test_case(9999, 1..=9999 => Some(9999) ; "upper bound")]
+    fn account_number_new_in_range(value: u32, range: RangeInclusive<u32>) -> Option<u32> {
+        Number::new_in_range(value, range).map(|x| x.number())
+    }
+
     #[test_case("No leading" => Some(Name(String::from("No leading"))))]
     #[test_case("   Leading" => Some(Name(String::from("Leading"))))]
     #[test_case("Trailing\t" => Some(Name(String::from("Trailing"))))]
     #[test_case("\n Both \n" => Some(Name(String::from("Both"))))]
     #[test_case("\n  \n" => None)]
+    #[test_case("Bank\u{0} account" => None ; "embedded nul")]
+    #[test_case("Bank\naccount" => None ; "embedded newline")]
     fn account_name_new(input: &str) -> Option<Name> {
         Name::new(input)
     }
+
+    #[test_case("0101" => Ok(101))]
+    #[test_case(" 500 " => Ok(500))]
+    #[test_case("1000.10" => Ok(100010))]
+    #[test_case("0" => Err(ParseNumberError::Zero))]
+    #[test_case("abc" => Err(ParseNumberError::Invalid))]
+    #[test_case("" => Err(ParseNumberError::Invalid))]
+    fn account_number_parse(input: &str) -> Result<u32, ParseNumberError> {
+        Number::parse(input).map(|x| x.number())
+    }
+
+    #[test_case(101, 4 => "0101")]
+    #[test_case(123456, 4 => "123456")]
+    #[test_case(101, 0 => "101")]
+    fn account_number_formatted(number: u32, width: usize) -> String {
+        Number::new(number).unwrap().formatted(width)
+    }
+
+    #[test]
+    fn account_name_new_should_limit_length() {
+        let longest = "a".repeat(Name::MAX_LENGTH);
+
+        assert_eq!(Name::new(&longest), Some(Name(longest.clone())));
+        assert_eq!(Name::new("a".repeat(200)), None);
+        assert_eq!(
+            Name::new(format!("  {longest}  ")),
+            Some(Name(longest)),
+            "surrounding whitespace doesn't count"
+        );
+    }
 }
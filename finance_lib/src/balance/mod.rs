@@ -1,5 +1,7 @@
 use std::{any::Any, convert::TryInto, iter::Sum, marker::PhantomData, num::NonZeroU32};
 
+use crate::error::{AmountError, ParseAmountError};
+
 /// A balance is either a Debit or Credit transaction
 ///
 /// # Examples
@@ -12,7 +14,8 @@ use std::{any::Any, convert::TryInto, iter::Sum, marker::PhantomData, num::NonZe
 /// assert_eq!(debit.amount(), 50);
 /// assert_eq!(credit.amount(), 20);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Balance {
     Debit(Transaction<Debit>),
     Credit(Transaction<Credit>),
@@ -35,6 +38,16 @@ impl Balance {
             .ok()
     }
 
+    /// Create a new debit balance reporting why the amount was rejected
+    pub fn try_debit<T: Into<u64>>(amount: T) -> Result<Self, AmountError> {
+        Transaction::try_debit(amount).map(Self::Debit)
+    }
+
+    /// Create a new credit balance reporting why the amount was rejected
+    pub fn try_credit<T: Into<u64>>(amount: T) -> Result<Self, AmountError> {
+        Transaction::try_credit(amount).map(Self::Credit)
+    }
+
     /// Get the amount of either the debit or credit
     pub fn amount(&self) -> u32 {
         match self {
@@ -42,6 +55,29 @@ impl Balance {
             Balance::Credit(x) => x.amount(),
         }
     }
+
+    /// The amount with its digits grouped by three using `,`, e.g. `12,345`
+    pub fn format_grouped(&self) -> String {
+        self.format_grouped_with(',')
+    }
+
+    /// The amount with its digits grouped by three using `separator`
+    pub fn format_grouped_with(&self, separator: char) -> String {
+        group_digits(self.amount(), separator)
+    }
+}
+
+fn group_digits(amount: u32, separator: char) -> String {
+    let digits = amount.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+
+    grouped
 }
 
 impl From<Transaction<Debit>> for Balance {
@@ -76,18 +112,60 @@ impl From<Box<Transaction<Credit>>> for Balance {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Debit;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Credit;
 
+#[allow(dead_code)]
 pub(crate) trait TransactionMarker: std::fmt::Debug {
     fn as_any(&self) -> &dyn Any;
 
     fn as_balance(&self) -> Balance;
 }
 
+/// Either a debit or a credit transaction decided at runtime
+///
+/// # Examples
+/// ```
+/// use personal_finance::balance::{AnyTransaction, Balance, Transaction};
+///
+/// let transactions: Vec<AnyTransaction> = vec![
+///     Transaction::debit(50).unwrap().into(),
+///     Transaction::credit(20).unwrap().into(),
+/// ];
+///
+/// let balances = transactions.into_iter().map(Balance::from).collect::<Vec<_>>();
+/// assert_eq!(balances, vec![Balance::debit(50).unwrap(), Balance::credit(20).unwrap()]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnyTransaction {
+    Debit(Transaction<Debit>),
+    Credit(Transaction<Credit>),
+}
+
+impl From<Transaction<Debit>> for AnyTransaction {
+    fn from(value: Transaction<Debit>) -> Self {
+        Self::Debit(value)
+    }
+}
+
+impl From<Transaction<Credit>> for AnyTransaction {
+    fn from(value: Transaction<Credit>) -> Self {
+        Self::Credit(value)
+    }
+}
+
+impl From<AnyTransaction> for Balance {
+    fn from(value: AnyTransaction) -> Self {
+        match value {
+            AnyTransaction::Debit(x) => Self::Debit(x),
+            AnyTransaction::Credit(x) => Self::Credit(x),
+        }
+    }
+}
+
 impl TransactionMarker for Transaction<Credit> {
     fn as_any(&self) -> &dyn Any {
         self
@@ -109,17 +187,53 @@ impl TransactionMarker for Transaction<Debit> {
 }
 
 /// Data for a single transaction holding the entry type and amount
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        into = "u32",
+        try_from = "u32",
+        bound(serialize = "T: Clone", deserialize = "")
+    )
+)]
 pub struct Transaction<T> {
     amount: u32,
     phantom: PhantomData<T>,
 }
 
+impl<T> TryFrom<u32> for Transaction<T> {
+    type Error = AmountError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        checked_amount(value.into()).map(|amount| Self {
+            amount,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<T> From<Transaction<T>> for u32 {
+    fn from(value: Transaction<T>) -> Self {
+        value.amount
+    }
+}
+
 impl<T> Transaction<T> {
     pub fn amount(&self) -> u32 {
         self.amount
     }
 
+    /// The amount with its digits grouped by three using `,`, e.g. `12,345`
+    pub fn format_grouped(&self) -> String {
+        self.format_grouped_with(',')
+    }
+
+    /// The amount with its digits grouped by three using `separator`
+    pub fn format_grouped_with(&self, separator: char) -> String {
+        group_digits(self.amount, separator)
+    }
+
     pub fn map<F>(self, f: F) -> Self
     where
         F: Fn(u32) -> u32,
@@ -150,6 +264,17 @@ impl Transaction<Debit> {
             .ok()
     }
 
+    /// Create a new debit transaction reporting why the amount was rejected
+    ///
+    /// # Examples
+    /// ```
+    /// use personal_finance::{balance::Transaction, error::AmountError};
+    /// assert_eq!(Transaction::try_debit(0u32), Err(AmountError::Zero));
+    /// ```
+    pub fn try_debit<T: Into<u64>>(amount: T) -> Result<Self, AmountError> {
+        checked_amount(amount.into()).map(Self::debit_unchecked)
+    }
+
     pub(crate) fn debit_unchecked(amount: u32) -> Self {
         assert!(amount != 0);
 
@@ -178,6 +303,11 @@ impl Transaction<Credit> {
             .ok()
     }
 
+    /// Create a new credit transaction reporting why the amount was rejected
+    pub fn try_credit<T: Into<u64>>(amount: T) -> Result<Self, AmountError> {
+        checked_amount(amount.into()).map(Self::credit_unchecked)
+    }
+
     pub(crate) fn credit_unchecked(amount: u32) -> Self {
         assert!(amount != 0);
 
@@ -246,6 +376,14 @@ impl<T> std::ops::AddAssign for Transaction<T> {
     }
 }
 
+fn checked_amount(amount: u64) -> Result<u32, AmountError> {
+    match u32::try_from(amount) {
+        Ok(0) => Err(AmountError::Zero),
+        Ok(amount) => Ok(amount),
+        Err(_) => Err(AmountError::TooLarge),
+    }
+}
+
 /// Split an iterator of Balance items into a tuple of Debits and Credits
 ///
 /// This returns a tuple where the first one is the debits and second is credits
@@ -267,5 +405,303 @@ where
         })
 }
 
+/// How fractions of a unit are rounded in money math
+///
+/// All helpers that divide amounts take a rounding so reports reconcile regardless
+/// of which helper produced a figure. The default is [Rounding::HalfUp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Round to the nearest unit, halves are rounded up
+    #[default]
+    HalfUp,
+    /// Round to the nearest unit, halves are rounded to the even unit
+    HalfEven,
+    /// Always round down
+    Floor,
+    /// Always round up
+    Ceil,
+}
+
+impl Rounding {
+    /// Divide `numerator` by `denominator` rounding the result
+    ///
+    /// # Panics
+    /// Panics if `denominator` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use personal_finance::balance::Rounding;
+    ///
+    /// assert_eq!(Rounding::HalfUp.divide(25, 10), 3);
+    /// assert_eq!(Rounding::HalfEven.divide(25, 10), 2);
+    /// ```
+    pub fn divide(self, numerator: u64, denominator: u64) -> u64 {
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        if remainder == 0 {
+            return quotient;
+        }
+
+        let round_up = match self {
+            Self::Floor => false,
+            Self::Ceil => true,
+            Self::HalfUp => remainder * 2 >= denominator,
+            Self::HalfEven => match (remainder * 2).cmp(&denominator) {
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Equal => quotient % 2 == 1,
+                std::cmp::Ordering::Greater => true,
+            },
+        };
+
+        if round_up {
+            quotient + 1
+        } else {
+            quotient
+        }
+    }
+}
+
+/// Number of minor units in one major unit of a book
+///
+/// Amounts are stored as whole minor units, a book tracking cents has a scale
+/// of 100 and a book in whole units a scale of 1. The scale is always a power
+/// of ten so amounts can be written as decimals.
+///
+/// # Examples
+/// ```
+/// use personal_finance::balance::Scale;
+///
+/// assert_eq!(Scale::CENTS.parse("12.34"), Ok(1234));
+/// assert_eq!(Scale::CENTS.format(1234), "12.34");
+/// assert_eq!(Scale::UNITS.format(1234), "1234");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Scale {
+    decimals: u32,
+}
+
+impl Scale {
+    pub const UNITS: Self = Self { decimals: 0 };
+    pub const CENTS: Self = Self { decimals: 2 };
+
+    /// Create a scale of `minor_units` per major unit
+    ///
+    /// Returns None unless `minor_units` is a power of ten.
+    pub fn new(minor_units: u32) -> Option<Self> {
+        let decimals = minor_units.checked_ilog10()?;
+        (10u32.pow(decimals) == minor_units).then_some(Self { decimals })
+    }
+
+    pub fn minor_units(&self) -> u32 {
+        10u32.pow(self.decimals)
+    }
+
+    /// Number of digits after the decimal point
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    /// Parse a decimal amount in major units into minor units
+    ///
+    /// Commas are taken as thousands separators and ignored. The amount may
+    /// have fewer decimals than the scale but not more.
+    pub fn parse(&self, amount: &str) -> Result<i64, ParseAmountError> {
+        let amount = amount.trim().replace(',', "");
+        let (negative, amount) = match amount.strip_prefix('-') {
+            Some(amount) => (true, amount),
+            None => (false, amount.as_str()),
+        };
+
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if whole.is_empty() && fraction.is_empty()
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|ch| ch.is_ascii_digit())
+        {
+            return Err(ParseAmountError::Invalid);
+        }
+        if fraction.len() > self.decimals as usize {
+            return Err(ParseAmountError::TooPrecise);
+        }
+
+        let parse = |digits: &str| match digits {
+            "" => Ok(0),
+            digits => digits
+                .parse::<i64>()
+                .map_err(|_| ParseAmountError::TooLarge),
+        };
+        let padding = 10i64.pow(self.decimals - fraction.len() as u32);
+        let amount = parse(whole)?
+            .checked_mul(i64::from(self.minor_units()))
+            .and_then(|x| x.checked_add(parse(fraction).ok()? * padding))
+            .ok_or(ParseAmountError::TooLarge)?;
+
+        Ok(if negative { -amount } else { amount })
+    }
+
+    /// Write an amount of minor units as a decimal in major units
+    pub fn format(&self, amount: i64) -> String {
+        let sign = if amount < 0 { "-" } else { "" };
+        let amount = amount.unsigned_abs();
+        let minor_units = u64::from(self.minor_units());
+
+        match self.decimals {
+            0 => format!("{sign}{amount}"),
+            decimals => format!(
+                "{sign}{}.{:0width$}",
+                amount / minor_units,
+                amount % minor_units,
+                width = decimals as usize
+            ),
+        }
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::UNITS
+    }
+}
+
+/// Compute the tax on `base` at a rate given in basis points (1/100 of a percent)
+///
+/// The tax is put on the opposite side of `base`, so the tax on a debit is a credit.
+/// Fractions are rounded according to `rounding`, with [Rounding::HalfUp] 1000 bps
+/// on 15 is 1.5 which becomes 2.
+/// Returns None if the tax rounds to zero since a balance can't be empty.
+///
+/// # Examples
+/// ```
+/// use personal_finance::balance::{tax_line, Balance, Rounding};
+///
+/// let tax = tax_line(Balance::debit(100).unwrap(), 2000, Rounding::default());
+/// assert_eq!(tax, Balance::credit(20));
+/// ```
+pub fn tax_line(base: Balance, rate_bps: u32, rounding: Rounding) -> Option<Balance> {
+    let tax = rounding.divide(u64::from(base.amount()) * u64::from(rate_bps), 10_000);
+    let tax = u32::try_from(tax).ok()?;
+
+    match base {
+        Balance::Debit(_) => Balance::credit(tax),
+        Balance::Credit(_) => Balance::debit(tax),
+    }
+}
+
+/// A signed sum of balances where debits are positive and credits are negative
+///
+/// Unlike [Balance] this can be zero, which makes it a starting point when
+/// adding balances together.
+///
+/// # Examples
+/// ```
+/// use personal_finance::balance::{Balance, NetBalance};
+///
+/// let net = NetBalance::ZERO + Balance::debit(50).unwrap() + Balance::credit(20).unwrap();
+/// assert_eq!(net.value(), 30);
+/// assert_eq!(net.into_balance(), Balance::debit(30));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct NetBalance(i64);
+
+impl NetBalance {
+    pub const ZERO: Self = Self(0);
+
+    pub fn new(value: i64) -> Self {
+        Self(value)
+    }
+
+    pub fn value(&self) -> i64 {
+        self.0
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Turn the net back into a debit or credit
+    ///
+    /// Returns None if the net is zero or too large to fit in a [Balance].
+    pub fn into_balance(self) -> Option<Balance> {
+        let amount = u32::try_from(self.0.unsigned_abs()).ok()?;
+        if self.0 > 0 {
+            Balance::debit(amount)
+        } else {
+            Balance::credit(amount)
+        }
+    }
+}
+
+impl From<Balance> for NetBalance {
+    fn from(balance: Balance) -> Self {
+        match balance {
+            Balance::Debit(x) => Self(i64::from(x.amount())),
+            Balance::Credit(x) => Self(-i64::from(x.amount())),
+        }
+    }
+}
+
+impl From<NetBalance> for i64 {
+    fn from(net: NetBalance) -> Self {
+        net.0
+    }
+}
+
+impl std::ops::Add for NetBalance {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Add<Balance> for NetBalance {
+    type Output = Self;
+
+    fn add(self, rhs: Balance) -> Self::Output {
+        self + Self::from(rhs)
+    }
+}
+
+impl std::ops::Sub for NetBalance {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl std::ops::Sub<Balance> for NetBalance {
+    type Output = Self;
+
+    fn sub(self, rhs: Balance) -> Self::Output {
+        self - Self::from(rhs)
+    }
+}
+
+impl std::ops::AddAssign for NetBalance {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::AddAssign<Balance> for NetBalance {
+    fn add_assign(&mut self, rhs: Balance) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sum for NetBalance {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, std::ops::Add::add)
+    }
+}
+
+impl Sum<Balance> for NetBalance {
+    fn sum<I: Iterator<Item = Balance>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, std::ops::Add::add)
+    }
+}
+
 #[cfg(test)]
 mod tests;
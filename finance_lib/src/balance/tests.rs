@@ -1,5 +1,7 @@
 use super::*;
 
+use crate::error::AmountError;
+
 use std::any::{Any, TypeId};
 use test_case::test_case;
 
@@ -51,7 +53,7 @@ fn transaction_credit_map<F: Fn(u32) -> u32>(amount: u32, f: F) -> u32 {
 
 #[test]
 fn sum_trait_iter() {
-    let vec = vec![
+    let vec = [
         Transaction::debit(50).unwrap(),
         Transaction::debit(20).unwrap(),
         Transaction::debit(30).unwrap(),
@@ -109,3 +111,168 @@ fn to_balance_should_return_credit_balance_given_transaction_credit() {
     let expected = Balance::Credit(Transaction::credit(50).unwrap());
     assert_eq!(actual, expected);
 }
+
+#[test_case(Balance::debit(100).unwrap(), 2000 => Balance::credit(20) ; "twenty percent of debit")]
+#[test_case(Balance::credit(100).unwrap(), 2000 => Balance::debit(20) ; "twenty percent of credit")]
+#[test_case(Balance::debit(15).unwrap(), 1000 => Balance::credit(2) ; "half rounds up")]
+#[test_case(Balance::debit(14).unwrap(), 1000 => Balance::credit(1) ; "below half rounds down")]
+#[test_case(Balance::debit(4).unwrap(), 1000 => None ; "zero tax")]
+fn tax_line_in_basis_points(base: Balance, rate_bps: u32) -> Option<Balance> {
+    tax_line(base, rate_bps, Rounding::default())
+}
+
+#[test_case(Rounding::HalfUp => vec![3, 4, 2, 5] ; "half up")]
+#[test_case(Rounding::HalfEven => vec![2, 4, 2, 5] ; "half even")]
+#[test_case(Rounding::Floor => vec![2, 3, 2, 5] ; "floor")]
+#[test_case(Rounding::Ceil => vec![3, 4, 3, 5] ; "ceil")]
+fn rounding_fractional_split(rounding: Rounding) -> Vec<u64> {
+    // 2.5, 3.5, 2.1 and exactly 5
+    [(25, 10), (35, 10), (21, 10), (50, 10)]
+        .into_iter()
+        .map(|(numerator, denominator)| rounding.divide(numerator, denominator))
+        .collect()
+}
+
+#[test_case(Rounding::Floor => Balance::credit(1) ; "floor")]
+#[test_case(Rounding::Ceil => Balance::credit(2) ; "ceil")]
+fn tax_line_with_rounding(rounding: Rounding) -> Option<Balance> {
+    tax_line(Balance::debit(14).unwrap(), 1000, rounding)
+}
+
+#[test]
+fn mixed_transactions_convert_to_balance() {
+    let transactions: Vec<AnyTransaction> = vec![
+        Transaction::debit(100).unwrap().into(),
+        Transaction::credit(40).unwrap().into(),
+        Transaction::credit(60).unwrap().into(),
+    ];
+
+    let balances = transactions
+        .into_iter()
+        .map(Balance::from)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        balances,
+        vec![
+            Balance::debit(100).unwrap(),
+            Balance::credit(40).unwrap(),
+            Balance::credit(60).unwrap(),
+        ]
+    );
+}
+
+#[test_case(0 => Err(AmountError::Zero))]
+#[test_case(100 => Ok(100))]
+#[test_case(u64::from(u32::MAX) => Ok(u32::MAX))]
+#[test_case(u64::from(u32::MAX) + 1 => Err(AmountError::TooLarge))]
+fn try_debit_reports_rejected_amount(amount: u64) -> Result<u32, AmountError> {
+    let transaction = Transaction::try_debit(amount).map(|x| x.amount());
+    assert_eq!(Balance::try_debit(amount).map(|x| x.amount()), transaction);
+    transaction
+}
+
+#[test_case(0 => Err(AmountError::Zero))]
+#[test_case(u64::MAX => Err(AmountError::TooLarge))]
+fn try_credit_reports_rejected_amount(amount: u64) -> Result<u32, AmountError> {
+    let transaction = Transaction::try_credit(amount).map(|x| x.amount());
+    assert_eq!(Balance::try_credit(amount).map(|x| x.amount()), transaction);
+    transaction
+}
+
+#[test]
+fn net_balance_of_equal_debits_and_credits_is_zero() {
+    let balances = [
+        Balance::debit(50).unwrap(),
+        Balance::credit(30).unwrap(),
+        Balance::credit(20).unwrap(),
+    ];
+
+    let net = balances.into_iter().sum::<NetBalance>();
+
+    assert_eq!(net, NetBalance::ZERO);
+    assert!(net.is_zero());
+    assert_eq!(net.into_balance(), None);
+}
+
+#[test_case(NetBalance::new(30) => Balance::debit(30) ; "positive is debit")]
+#[test_case(NetBalance::new(-30) => Balance::credit(30) ; "negative is credit")]
+#[test_case(NetBalance::new(i64::from(u32::MAX) + 1) => None ; "too large")]
+fn net_balance_into_balance(net: NetBalance) -> Option<Balance> {
+    net.into_balance()
+}
+
+#[test]
+fn net_balance_subtracting_a_balance_reverses_it() {
+    let debit = Balance::debit(40).unwrap();
+
+    assert_eq!(NetBalance::ZERO + debit - debit, NetBalance::ZERO);
+    assert_eq!((NetBalance::ZERO - debit).value(), -40);
+}
+
+#[test_case(Scale::CENTS, "12.34" => Ok(1234) ; "cents")]
+#[test_case(Scale::CENTS, "-1,000.5" => Ok(-100050) ; "negative with separator")]
+#[test_case(Scale::CENTS, "12.345" => Err(ParseAmountError::TooPrecise) ; "too precise")]
+#[test_case(Scale::UNITS, "12" => Ok(12) ; "units")]
+#[test_case(Scale::UNITS, "12.3" => Err(ParseAmountError::TooPrecise) ; "units with decimals")]
+#[test_case(Scale::new(1000).unwrap(), ".5" => Ok(500) ; "thousandths")]
+#[test_case(Scale::CENTS, "-" => Err(ParseAmountError::Invalid) ; "only sign")]
+#[test_case(Scale::CENTS, "1e3" => Err(ParseAmountError::Invalid) ; "not decimal")]
+#[test_case(Scale::CENTS, "99999999999999999999" => Err(ParseAmountError::TooLarge) ; "too large")]
+fn scale_parse(scale: Scale, amount: &str) -> Result<i64, ParseAmountError> {
+    scale.parse(amount)
+}
+
+#[test_case(Scale::CENTS, 1234 => "12.34")]
+#[test_case(Scale::CENTS, -5 => "-0.05")]
+#[test_case(Scale::UNITS, -1234 => "-1234")]
+#[test_case(Scale::new(1000).unwrap(), 1500 => "1.500")]
+fn scale_format(scale: Scale, amount: i64) -> String {
+    scale.format(amount)
+}
+
+#[test_case(1 => Some(0))]
+#[test_case(100 => Some(2))]
+#[test_case(250 => None)]
+#[test_case(0 => None)]
+fn scale_new(minor_units: u32) -> Option<u32> {
+    Scale::new(minor_units).map(|x| x.decimals())
+}
+
+#[test]
+fn scale_round_trips_cents() {
+    let minor = Scale::CENTS.parse("12.34").unwrap();
+
+    assert_eq!(minor, 1234);
+    assert_eq!(Scale::CENTS.format(minor), "12.34");
+}
+
+#[test]
+fn balances_should_deduplicate_in_a_hash_set() {
+    let balances = [
+        Balance::debit(50).unwrap(),
+        Balance::debit(50).unwrap(),
+        Balance::credit(50).unwrap(),
+    ]
+    .into_iter()
+    .collect::<std::collections::HashSet<_>>();
+
+    assert_eq!(balances.len(), 2);
+}
+
+#[test_case(1 => "1")]
+#[test_case(123 => "123")]
+#[test_case(1234 => "1,234")]
+#[test_case(123456 => "123,456")]
+#[test_case(1234567 => "1,234,567")]
+fn balance_format_grouped(amount: u32) -> String {
+    Balance::debit(amount).unwrap().format_grouped()
+}
+
+#[test]
+fn transaction_format_grouped_with_separator() {
+    let transaction = Transaction::credit(1234567).unwrap();
+
+    assert_eq!(transaction.format_grouped_with(' '), "1 234 567");
+    assert_eq!(transaction.format_grouped(), "1,234,567");
+}
@@ -1,14 +1,18 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::mem;
 
 use chrono::prelude::*;
 
 use crate::{
     account::{self, Category},
-    balance::{Balance, Transaction},
-    error::JournalValidationError,
+    balance::{Balance, NetBalance, Transaction},
+    error::{CombineError, JournalValidationError},
 };
 
+pub use template::{JournalTemplate, Share};
+
+mod template;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct EntryDetails {
     date: Date<Utc>,
@@ -16,11 +20,16 @@ struct EntryDetails {
 }
 
 /// An account with a name and identifier
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Equality compares all fields of the account, use [Account::same_account] to
+/// check if two accounts are the same account regardless of name and category.
+/// Accounts are active when created, a closed account is inactive.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Account {
     number: account::Number,
     name: account::Name,
     category: Category,
+    active: bool,
 }
 
 impl Account {
@@ -33,9 +42,20 @@ impl Account {
             number: number.into(),
             name,
             category: element,
+            active: true,
         }
     }
 
+    pub fn with_active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    pub fn with_category(mut self, category: Category) -> Self {
+        self.category = category;
+        self
+    }
+
     pub fn number(&self) -> account::Number {
         self.number
     }
@@ -47,6 +67,15 @@ impl Account {
     pub fn category(&self) -> Category {
         self.category
     }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns true if both accounts have the same number
+    pub fn same_account(&self, other: &Account) -> bool {
+        self.number == other.number
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -77,6 +106,10 @@ impl Chart {
         }
     }
 
+    pub fn get<T: Into<account::Number>>(&self, id: T) -> Option<&Account> {
+        self.chart.get(&id.into().number())
+    }
+
     /// Remove an account from the chart
     pub fn remove<T: Into<account::Number>>(&mut self, id: T) {
         self.chart.remove(&id.into().number());
@@ -85,6 +118,172 @@ impl Chart {
     pub fn iter(&self) -> impl Iterator<Item = &Account> {
         self.chart.values()
     }
+
+    /// Find all accounts whose name contains `query`, ignoring case
+    ///
+    /// The accounts are ordered by their number.
+    pub fn search(&self, query: &str) -> Vec<&Account> {
+        let query = query.to_lowercase();
+        self.chart
+            .values()
+            .filter(|account| account.name().as_str().to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Find the first account named `name`, ignoring case and surrounding whitespace
+    ///
+    /// Names aren't unique, see [Chart::find_all_by_name] to get every match.
+    pub fn find_by_name(&self, name: &str) -> Option<&Account> {
+        self.find_all_by_name(name).into_iter().next()
+    }
+
+    /// Find all accounts named `name`, ignoring case and surrounding whitespace
+    ///
+    /// The accounts are ordered by their number.
+    pub fn find_all_by_name(&self, name: &str) -> Vec<&Account> {
+        let name = name.trim().to_lowercase();
+        self.chart
+            .values()
+            .filter(|account| account.name().as_str().to_lowercase() == name)
+            .collect()
+    }
+
+    /// Suggest the next account number for a category
+    ///
+    /// Returns the smallest unused number in the conventional range of the category,
+    /// see [Category::number_range], or None if every number is taken.
+    pub fn next_number(&self, category: Category) -> Option<account::Number> {
+        category
+            .number_range()
+            .find(|x| !self.chart.contains_key(x))
+            .and_then(account::Number::new)
+    }
+
+    /// Merge all accounts from another chart into this chart
+    ///
+    /// Accounts with the same number are overwritten by the account in `other`,
+    /// the same as with [Chart::insert]. Returns the numbers that collided.
+    pub fn merge(&mut self, other: Chart) -> Vec<account::Number> {
+        self.merge_with(other, ConflictPolicy::TakeOther)
+    }
+
+    /// Merge all accounts from another chart into this chart resolving
+    /// accounts with the same number according to `policy`.
+    ///
+    /// Returns the numbers that collided.
+    pub fn merge_with(&mut self, other: Chart, policy: ConflictPolicy) -> Vec<account::Number> {
+        let mut collisions = Vec::new();
+        for (key, account) in other.chart {
+            match self.chart.get_mut(&key) {
+                Some(existing) => {
+                    collisions.push(account.number);
+                    if policy == ConflictPolicy::TakeOther {
+                        *existing = account;
+                    }
+                }
+                None => {
+                    self.chart.insert(key, account);
+                }
+            }
+        }
+
+        collisions
+    }
+
+    /// Render the chart as a table for terminal output
+    ///
+    /// Accounts are grouped under a heading for each category in number order.
+    /// The columns are as wide as their widest value.
+    ///
+    /// # Examples
+    /// ```
+    /// use personal_finance::{
+    ///     account::{Category, Name, Number},
+    ///     entry::{Account, Chart},
+    /// };
+    ///
+    /// let chart = [Account::new(Number::new(101).unwrap(), Name::new("Cash").unwrap(), Category::Asset)]
+    ///     .into_iter()
+    ///     .collect::<Chart>();
+    ///
+    /// assert_eq!(chart.to_table(), "Number  Name  Category\nAsset\n101     Cash  Asset\n");
+    /// ```
+    pub fn to_table(&self) -> String {
+        const NUMBER: &str = "Number";
+        const NAME: &str = "Name";
+
+        let number_width = self
+            .chart
+            .keys()
+            .map(|x| x.to_string().len())
+            .chain([NUMBER.len()])
+            .max()
+            .unwrap_or_default();
+        let name_width = self
+            .chart
+            .values()
+            .map(|x| x.name().as_str().chars().count())
+            .chain([NAME.len()])
+            .max()
+            .unwrap_or_default();
+
+        let by_category = self.chart.values().fold(
+            BTreeMap::<Category, Vec<&Account>>::new(),
+            |mut categories, account| {
+                categories
+                    .entry(account.category())
+                    .or_default()
+                    .push(account);
+                categories
+            },
+        );
+
+        let mut table = format!("{NUMBER:<number_width$}  {NAME:<name_width$}  Category\n");
+        for (category, accounts) in by_category {
+            table.push_str(&format!("{category}\n"));
+            for account in accounts {
+                table.push_str(&format!(
+                    "{:<number_width$}  {:<name_width$}  {}\n",
+                    account.number().number(),
+                    account.name().as_str(),
+                    account.category(),
+                ));
+            }
+        }
+
+        table
+    }
+}
+
+impl IntoIterator for Chart {
+    type IntoIter = std::collections::btree_map::IntoValues<u32, Account>;
+    type Item = Account;
+
+    /// Iterate over the accounts in number order
+    fn into_iter(self) -> Self::IntoIter {
+        self.chart.into_values()
+    }
+}
+
+/// Accounts with the same number are replaced by the later account, same as [Chart::insert]
+impl FromIterator<Account> for Chart {
+    fn from_iter<T: IntoIterator<Item = Account>>(iter: T) -> Self {
+        let mut chart = Chart::new();
+        for account in iter {
+            chart.insert(account);
+        }
+
+        chart
+    }
+}
+
+/// Decides which account to keep when merging two charts with the same account number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the account already in the chart
+    KeepExisting,
+    /// Replace the account with the one being merged in
+    TakeOther,
 }
 
 /// This describes a "line" in a journal and notes one account being affected
@@ -113,6 +312,62 @@ impl<'a> JournalEntry<'a> {
     }
 }
 
+/// An informational line in a journal that doesn't affect any balance
+///
+/// Amounts of a [Balance] can't be zero so notes are kept apart from the
+/// [JournalEntry] lines, [Journal::lines] yields both in the order they were
+/// pushed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalNote<'a> {
+    account: &'a Account,
+    text: String,
+    /// Number of entries before the note
+    position: usize,
+}
+
+impl JournalNote<'_> {
+    pub fn account(&self) -> &Account {
+        self.account
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// A line of a journal, either an entry or a note
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalLine<'a> {
+    Entry(&'a JournalEntry<'a>),
+    Note(&'a JournalNote<'a>),
+}
+
+impl JournalLine<'_> {
+    /// The account of the entry or note
+    pub fn account(&self) -> &Account {
+        match self {
+            Self::Entry(entry) => entry.account(),
+            Self::Note(note) => note.account(),
+        }
+    }
+}
+
+/// Interleave the notes with the entries they were pushed between
+fn lines<'a>(
+    entries: &'a [JournalEntry<'a>],
+    notes: &'a [JournalNote<'a>],
+) -> impl Iterator<Item = JournalLine<'a>> {
+    let mut entries = entries.iter().enumerate().peekable();
+    let mut notes = notes.iter().peekable();
+    std::iter::from_fn(move || match (entries.peek(), notes.peek()) {
+        (Some((index, _)), Some(note)) if note.position <= *index => {
+            notes.next().map(JournalLine::Note)
+        }
+        (Some(_), _) => entries.next().map(|(_, entry)| JournalLine::Entry(entry)),
+        (None, _) => notes.next().map(JournalLine::Note),
+    })
+}
+
 /// Journal is an entry into the bookkeeping.
 ///
 /// This describes which accounts is being debited and which account is being credited
@@ -127,6 +382,7 @@ impl<'a> JournalEntry<'a> {
 pub struct Journal<'a> {
     details: EntryDetails,
     entries: Vec<JournalEntry<'a>>,
+    notes: Vec<JournalNote<'a>>,
 }
 
 impl<'a> Journal<'a> {
@@ -137,6 +393,7 @@ impl<'a> Journal<'a> {
                 description: None,
             },
             entries: Vec::new(),
+            notes: Vec::new(),
         }
     }
 
@@ -148,6 +405,10 @@ impl<'a> Journal<'a> {
         self.details.description.as_ref()
     }
 
+    pub fn set_date(&mut self, date: Date<Utc>) {
+        self.details.date = date;
+    }
+
     pub fn date(&self) -> &Date<Utc> {
         &self.details.date
     }
@@ -159,14 +420,116 @@ impl<'a> Journal<'a> {
         self.entries.push(JournalEntry::new(account, transaction));
     }
 
-    pub fn as_slice(&self) -> &[JournalEntry] {
+    /// Remove the last entry and return it, or None if the journal has no entries
+    pub fn pop(&mut self) -> Option<JournalEntry<'a>> {
+        self.entries
+            .len()
+            .checked_sub(1)
+            .and_then(|last| self.remove(last))
+    }
+
+    /// Remove the entry at `index` and return it, or None if it is out of bounds
+    ///
+    /// The entries after it are shifted down to keep their order.
+    pub fn remove(&mut self, index: usize) -> Option<JournalEntry<'a>> {
+        (index < self.entries.len()).then(|| {
+            self.notes
+                .iter_mut()
+                .filter(|note| note.position > index)
+                .for_each(|note| note.position -= 1);
+            self.entries.remove(index)
+        })
+    }
+
+    /// Annotate an account without affecting the balance of the journal
+    ///
+    /// Notes are ignored by [Journal::validate] but kept in the validated journal.
+    pub fn push_note<T: Into<String>>(&mut self, account: &'a Account, text: T) {
+        self.notes.push(JournalNote {
+            account,
+            text: text.into(),
+            position: self.entries.len(),
+        });
+    }
+
+    /// The notes of the journal in the order they were added
+    pub fn notes(&self) -> &[JournalNote<'_>] {
+        self.notes.as_slice()
+    }
+
+    pub fn as_slice(&self) -> &[JournalEntry<'_>] {
         self.entries.as_slice()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &JournalEntry> {
+    pub fn iter(&self) -> impl Iterator<Item = &JournalEntry<'_>> {
         self.entries.iter()
     }
 
+    /// The entries and notes in the order they were pushed
+    pub fn lines(&self) -> impl Iterator<Item = JournalLine<'_>> {
+        lines(&self.entries, &self.notes)
+    }
+
+    /// Number of entries in the journal, notes are not counted
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The distinct account numbers used by the entries
+    pub fn accounts(&self) -> BTreeSet<u32> {
+        self.entries
+            .iter()
+            .map(|x| x.account().number().number())
+            .collect()
+    }
+
+    /// Combine two journals on the same date into one compound journal
+    ///
+    /// The entries of `other` are appended after the entries of this journal.
+    /// The description of this journal is kept, or taken from `other` if this
+    /// journal has none.
+    pub fn combine(mut self, other: Journal<'a>) -> Result<Journal<'a>, CombineError> {
+        if self.details.date != other.details.date {
+            return Err(CombineError {
+                left: self.details.date,
+                right: other.details.date,
+            });
+        }
+
+        if self.details.description.is_none() {
+            self.details.description = other.details.description;
+        }
+        let offset = self.entries.len();
+        self.entries.extend(other.entries);
+        self.notes
+            .extend(other.notes.into_iter().map(|note| JournalNote {
+                position: note.position + offset,
+                ..note
+            }));
+
+        Ok(self)
+    }
+
+    /// Check if the debits and credits are equal without consuming the journal
+    pub fn is_balanced(&self) -> bool {
+        self.imbalance() == 0
+    }
+
+    /// The amount the debits exceed the credits with, negative if the credits are larger
+    pub fn imbalance(&self) -> i64 {
+        self.entries
+            .iter()
+            .map(|x| match &x.transaction {
+                Balance::Debit(x) => i64::from(x.amount()),
+                Balance::Credit(x) => -i64::from(x.amount()),
+            })
+            .sum()
+    }
+
     pub fn validate(self) -> Result<ValidatedJournal<'a>, JournalValidationError> {
         let balance = self
             .entries
@@ -180,6 +543,7 @@ impl<'a> Journal<'a> {
             Ok(ValidatedJournal {
                 details: self.details,
                 entries: self.entries,
+                notes: self.notes,
             })
         } else {
             Err(JournalValidationError {
@@ -208,13 +572,39 @@ impl<'a> IntoIterator for &'a Journal<'a> {
     }
 }
 
+/// A journal where the debits and credits are balanced
+///
+/// Equality compares the entries in order, use [ValidatedJournal::equivalent]
+/// to ignore the order of the entries.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ValidatedJournal<'b> {
     details: EntryDetails,
     entries: Vec<JournalEntry<'b>>,
+    notes: Vec<JournalNote<'b>>,
 }
 
 impl ValidatedJournal<'_> {
+    /// Returns true if both journals have the same date, description, entries
+    /// and notes regardless of the order of the entries
+    pub fn equivalent(&self, other: &ValidatedJournal<'_>) -> bool {
+        fn sorted<'a>(entries: &'a [JournalEntry<'_>]) -> Vec<(&'a Account, &'a Balance)> {
+            let mut entries = entries
+                .iter()
+                .map(|x| (x.account(), x.balance()))
+                .collect::<Vec<_>>();
+            entries.sort();
+            entries
+        }
+
+        fn texts<'a>(notes: &'a [JournalNote<'_>]) -> Vec<(&'a Account, &'a str)> {
+            notes.iter().map(|x| (x.account(), x.text())).collect()
+        }
+
+        self.details == other.details
+            && sorted(&self.entries) == sorted(&other.entries)
+            && texts(&self.notes) == texts(&other.notes)
+    }
+
     pub fn description(&self) -> Option<&String> {
         self.details.description.as_ref()
     }
@@ -223,13 +613,38 @@ impl ValidatedJournal<'_> {
         &self.details.date
     }
 
-    pub fn as_slice(&self) -> &[JournalEntry] {
+    pub fn as_slice(&self) -> &[JournalEntry<'_>] {
         self.entries.as_slice()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &JournalEntry> {
+    pub fn iter(&self) -> impl Iterator<Item = &JournalEntry<'_>> {
         self.entries.iter()
     }
+
+    /// The entries and notes in the order they were pushed
+    pub fn lines(&self) -> impl Iterator<Item = JournalLine<'_>> {
+        lines(&self.entries, &self.notes)
+    }
+
+    pub fn notes(&self) -> &[JournalNote<'_>] {
+        self.notes.as_slice()
+    }
+
+    /// The net amount of each account in the journal
+    ///
+    /// Debits are positive and credits are negative, split lines on the same
+    /// account are summed to one figure.
+    pub fn net_by_account(&self) -> BTreeMap<&Account, i64> {
+        self.entries
+            .iter()
+            .fold(BTreeMap::new(), |mut accounts, entry| {
+                *accounts.entry(entry.account()).or_insert(NetBalance::ZERO) += *entry.balance();
+                accounts
+            })
+            .into_iter()
+            .map(|(account, net)| (account, net.value()))
+            .collect()
+    }
 }
 
 impl<'a> IntoIterator for ValidatedJournal<'a> {
@@ -252,7 +667,7 @@ impl<'a> IntoIterator for &'a ValidatedJournal<'a> {
 
 impl PartialEq<Journal<'_>> for ValidatedJournal<'_> {
     fn eq(&self, other: &Journal<'_>) -> bool {
-        self.details == other.details && self.entries == other.entries
+        self.details == other.details && self.entries == other.entries && self.notes == other.notes
     }
 }
 
@@ -332,6 +747,7 @@ mod test {
             name: account::Name::new(String::from("Test")).unwrap(),
             number: account::Number::new(54).unwrap(),
             category: Category::Asset,
+            active: true,
         };
 
         let tx = if is_debit(&tx) {
@@ -351,7 +767,7 @@ mod test {
     }
 
     #[test_case(Transaction::credit(50).unwrap(), Transaction::credit(50).unwrap())]
-    fn journal_entry_credit<T: 'static, 'a>(tx: Transaction<T>, expected: Transaction<Credit>)
+    fn journal_entry_credit<T: 'static>(tx: Transaction<T>, expected: Transaction<Credit>)
     where
         Transaction<T>: TransactionMarker,
     {
@@ -359,6 +775,7 @@ mod test {
             name: account::Name::new(String::from("Test")).unwrap(),
             number: account::Number::new(54).unwrap(),
             category: Category::Asset,
+            active: true,
         };
 
         let tx = if is_debit(&tx) {
@@ -377,6 +794,42 @@ mod test {
         assert_eq!(actual.balance(), &Balance::Credit(expected));
     }
 
+    #[test]
+    fn chart_to_table_groups_accounts_by_category() {
+        let chart = [
+            (501, "Groceries", Category::Expenses),
+            (102, "Savings account", Category::Asset),
+            (201, "Credit card", Category::Liability),
+            (101, "Cash", Category::Asset),
+        ]
+        .into_iter()
+        .map(|(number, name, category)| {
+            Account::new(
+                account::Number::new(number).unwrap(),
+                account::Name::new(name).unwrap(),
+                category,
+            )
+        })
+        .collect::<Chart>();
+
+        let table = chart.to_table();
+        let lines = table.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Number  Name             Category",
+                "Asset",
+                "101     Cash             Asset",
+                "102     Savings account  Asset",
+                "Liability",
+                "201     Credit card      Liability",
+                "Expenses",
+                "501     Groceries        Expenses",
+            ]
+        );
+    }
+
     #[test]
     fn chart_insert_duplicate_gives_length_one() {
         let mut chart = Chart::new();
@@ -511,4 +964,157 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    fn chart_from(accounts: &[(u32, &str)]) -> Chart {
+        let mut chart = Chart::new();
+        for (number, name) in accounts {
+            chart.insert(Account::new(
+                account::Number::new(*number).unwrap(),
+                account::Name::new(name).unwrap(),
+                Category::Asset,
+            ));
+        }
+
+        chart
+    }
+
+    #[test]
+    fn chart_merge_disjoint_charts_has_no_collisions() {
+        let mut chart = chart_from(&[(101, "Bank Account"), (102, "Cash")]);
+        let other = chart_from(&[(201, "Credit Loan")]);
+
+        let collisions = chart.merge(other);
+
+        assert!(collisions.is_empty());
+        assert_eq!(
+            chart
+                .iter()
+                .map(|x| x.number().number())
+                .collect::<Vec<_>>(),
+            vec![101, 102, 201]
+        );
+    }
+
+    #[test_case(ConflictPolicy::KeepExisting => "Bank Account")]
+    #[test_case(ConflictPolicy::TakeOther => "Savings")]
+    fn chart_merge_with_overlapping_numbers(policy: ConflictPolicy) -> String {
+        let mut chart = chart_from(&[(101, "Bank Account"), (102, "Cash")]);
+        let other = chart_from(&[(101, "Savings"), (201, "Credit Loan")]);
+
+        let collisions = chart.merge_with(other, policy);
+
+        assert_eq!(collisions, vec![account::Number::new(101).unwrap()]);
+        assert_eq!(chart.chart.len(), 3);
+        chart.chart[&101].name().as_str().to_owned()
+    }
+
+    #[test]
+    fn chart_next_number_skips_taken_numbers() {
+        let chart = chart_from(&[(101, "Bank Account"), (102, "Cash"), (201, "Loan")]);
+
+        assert_eq!(
+            chart.next_number(Category::Asset),
+            account::Number::new(103)
+        );
+        assert_eq!(
+            chart.next_number(Category::Expenses),
+            account::Number::new(501)
+        );
+    }
+
+    #[test]
+    fn chart_next_number_exhausted_range_is_none() {
+        let mut chart = Chart::new();
+        for number in Category::Equity.number_range() {
+            chart.insert(Account::new(
+                account::Number::new(number).unwrap(),
+                account::Name::new("Equity").unwrap(),
+                Category::Equity,
+            ));
+        }
+
+        assert_eq!(chart.next_number(Category::Equity), None);
+    }
+
+    #[test_case("bank" => vec![101, 301] ; "matches both banks")]
+    #[test_case("INVEST" => vec![301] ; "ignores case")]
+    #[test_case("loan" => Vec::<u32>::new() ; "no match")]
+    fn chart_search(query: &str) -> Vec<u32> {
+        let chart = chart_from(&[
+            (301, "Investment Bank"),
+            (101, "Bank Account"),
+            (102, "Cash"),
+        ]);
+
+        chart
+            .search(query)
+            .into_iter()
+            .map(|x| x.number().number())
+            .collect()
+    }
+
+    #[test_case("cash" => Some(102) ; "exact")]
+    #[test_case("  CASH\t" => Some(102) ; "ignores case and whitespace")]
+    #[test_case("bank" => None ; "no partial match")]
+    fn chart_find_by_name(name: &str) -> Option<u32> {
+        let chart = chart_from(&[(101, "Bank Account"), (102, " Cash ")]);
+
+        chart.find_by_name(name).map(|x| x.number().number())
+    }
+
+    #[test]
+    fn chart_find_all_by_name_returns_duplicates_in_order() {
+        let chart = chart_from(&[(301, "Savings"), (101, "savings"), (102, "Cash")]);
+
+        let actual = chart
+            .find_all_by_name(" SAVINGS ")
+            .into_iter()
+            .map(|x| x.number().number())
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, vec![101, 301]);
+    }
+
+    #[test]
+    fn equal_accounts_should_hash_the_same() {
+        let account = || {
+            Account::new(
+                account::Number::new(101).unwrap(),
+                account::Name::new("Bank Account").unwrap(),
+                Category::Asset,
+            )
+        };
+
+        let accounts = [account(), account()]
+            .into_iter()
+            .collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(accounts.len(), 1);
+    }
+
+    #[test]
+    fn chart_from_iter_keeps_the_last_duplicate() {
+        let account = |number, name| {
+            Account::new(
+                account::Number::new(number).unwrap(),
+                account::Name::new(name).unwrap(),
+                Category::Asset,
+            )
+        };
+
+        let chart = vec![
+            account(102, "Cash"),
+            account(101, "Bank Account"),
+            account(102, "Petty cash"),
+        ]
+        .into_iter()
+        .collect::<Chart>();
+
+        let actual = chart.into_iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            actual,
+            vec![account(101, "Bank Account"), account(102, "Petty cash")]
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use chrono::prelude::*;
+
+use crate::balance::{Balance, Rounding};
+
+use super::{Account, Journal};
+
+/// How much of the instantiated amount a line of a [JournalTemplate] receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Share {
+    /// The whole amount
+    Full,
+    /// A percentage of the amount, rounded with the rounding of the template
+    Percent(u32),
+    /// What remains of the amount after all [Share::Percent] lines on the same side
+    Remainder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Debit,
+    Credit,
+}
+
+#[derive(Debug, Clone)]
+struct TemplateLine<'a> {
+    account: &'a Account,
+    side: Side,
+    share: Share,
+}
+
+/// A reusable shape of a journal where only the amount and date changes
+///
+/// # Examples
+/// ```
+/// use chrono::prelude::*;
+/// use personal_finance::{
+///     account::{Category, Name, Number},
+///     entry::{Account, JournalTemplate, Share},
+/// };
+///
+/// let cash = Account::new(Number::new(101).unwrap(), Name::new("Cash").unwrap(), Category::Asset);
+/// let revenue = Account::new(Number::new(401).unwrap(), Name::new("Sales").unwrap(), Category::Income);
+///
+/// let mut sale = JournalTemplate::new();
+/// sale.debit(&cash, Share::Full);
+/// sale.credit(&revenue, Share::Full);
+///
+/// let journal = sale.instantiate(250, Utc.ymd(2022, 3, 14));
+/// assert!(journal.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JournalTemplate<'a> {
+    description: Option<String>,
+    rounding: Rounding,
+    lines: Vec<TemplateLine<'a>>,
+}
+
+impl<'a> JournalTemplate<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_description<T: Into<String>>(&mut self, description: T) {
+        self.description = Some(description.into());
+    }
+
+    /// Set how percentage lines are rounded, the default is [Rounding::HalfUp]
+    pub fn set_rounding(&mut self, rounding: Rounding) {
+        self.rounding = rounding;
+    }
+
+    pub fn debit(&mut self, account: &'a Account, share: Share) {
+        self.lines.push(TemplateLine {
+            account,
+            side: Side::Debit,
+            share,
+        });
+    }
+
+    pub fn credit(&mut self, account: &'a Account, share: Share) {
+        self.lines.push(TemplateLine {
+            account,
+            side: Side::Credit,
+            share,
+        });
+    }
+
+    /// Create a journal distributing `amount` over the lines of the template
+    ///
+    /// Lines that end up with a zero amount are left out of the journal.
+    pub fn instantiate(&self, amount: u32, date: Date<Utc>) -> Journal<'a> {
+        let mut journal = Journal::new(date);
+        if let Some(description) = &self.description {
+            journal.set_description(description.clone());
+        }
+
+        for line in &self.lines {
+            let value = match line.share {
+                Share::Full => amount,
+                Share::Percent(percent) => percentage(amount, percent, self.rounding),
+                Share::Remainder => amount.saturating_sub(
+                    self.lines
+                        .iter()
+                        .filter(|x| x.side == line.side)
+                        .filter_map(|x| match x.share {
+                            Share::Percent(percent) => {
+                                Some(percentage(amount, percent, self.rounding))
+                            }
+                            _ => None,
+                        })
+                        .fold(0, u32::saturating_add),
+                ),
+            };
+
+            let balance = match line.side {
+                Side::Debit => Balance::debit(value),
+                Side::Credit => Balance::credit(value),
+            };
+
+            if let Some(balance) = balance {
+                journal.push(line.account, balance);
+            }
+        }
+
+        journal
+    }
+}
+
+fn percentage(amount: u32, percent: u32, rounding: Rounding) -> u32 {
+    let value = rounding.divide(u64::from(amount) * u64::from(percent), 100);
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::account::{Category, Name, Number};
+
+    fn account(number: u32, name: &str, category: Category) -> Account {
+        Account::new(
+            Number::new(number).unwrap(),
+            Name::new(name).unwrap(),
+            category,
+        )
+    }
+
+    #[test]
+    fn sale_template_should_balance_with_tax_split() {
+        let cash = account(101, "Cash", Category::Asset);
+        let tax = account(201, "Sales tax", Category::Liability);
+        let revenue = account(401, "Sales", Category::Income);
+
+        let mut sale = JournalTemplate::new();
+        sale.set_description("Sale");
+        sale.debit(&cash, Share::Full);
+        sale.credit(&revenue, Share::Remainder);
+        sale.credit(&tax, Share::Percent(20));
+
+        let journal = sale.instantiate(1250, Utc.ymd(2022, 3, 14));
+
+        let lines = journal
+            .iter()
+            .map(|x| (x.account().number().number(), *x.balance()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (101, Balance::debit(1250).unwrap()),
+                (401, Balance::credit(1000).unwrap()),
+                (201, Balance::credit(250).unwrap()),
+            ]
+        );
+        assert_eq!(journal.description(), Some(&String::from("Sale")));
+        assert!(journal.validate().is_ok());
+    }
+
+    #[test]
+    fn zero_amount_lines_are_left_out() {
+        let cash = account(101, "Cash", Category::Asset);
+        let tax = account(201, "Sales tax", Category::Liability);
+        let revenue = account(401, "Sales", Category::Income);
+
+        let mut sale = JournalTemplate::new();
+        sale.debit(&cash, Share::Full);
+        sale.credit(&revenue, Share::Remainder);
+        sale.credit(&tax, Share::Percent(0));
+
+        let journal = sale.instantiate(100, Utc.ymd(2022, 3, 14));
+
+        assert_eq!(journal.as_slice().len(), 2);
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::prelude::*;
 use thiserror::Error;
 
 use crate::balance::{Credit, Debit, Transaction};
@@ -18,3 +19,60 @@ impl JournalValidationError {
         &self.credit
     }
 }
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("can not combine journals dated {left} and {right}")]
+pub struct CombineError {
+    pub(crate) left: Date<Utc>,
+    pub(crate) right: Date<Utc>,
+}
+
+impl CombineError {
+    pub fn left(&self) -> &Date<Utc> {
+        &self.left
+    }
+
+    pub fn right(&self) -> &Date<Utc> {
+        &self.right
+    }
+}
+
+/// Reason an amount can't be used for a transaction
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("amount can not be zero")]
+    Zero,
+    #[error("amount is larger than {}", u32::MAX)]
+    TooLarge,
+}
+
+/// A string that isn't a valid [Name](crate::account::Name)
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("'{0}' is not a valid account name")]
+pub struct NameError(pub String);
+
+/// Reason a QIF statement couldn't be parsed
+#[derive(Debug, Error)]
+pub enum QifError {
+    #[error("unable to read statement")]
+    Io(#[from] std::io::Error),
+    #[error("invalid date '{value}' on line {line}")]
+    InvalidDate { line: usize, value: String },
+    #[error("invalid amount '{value}' on line {line}")]
+    InvalidAmount { line: usize, value: String },
+    #[error("record ending on line {line} has no date")]
+    MissingDate { line: usize },
+    #[error("record ending on line {line} has no amount")]
+    MissingAmount { line: usize },
+}
+
+/// Reason a decimal amount couldn't be parsed with a [Scale](crate::balance::Scale)
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ParseAmountError {
+    #[error("amount is not a decimal number")]
+    Invalid,
+    #[error("amount has more decimals than the scale")]
+    TooPrecise,
+    #[error("amount is too large")]
+    TooLarge,
+}
@@ -0,0 +1,234 @@
+use std::io::BufRead;
+
+use chrono::prelude::*;
+
+use crate::{balance::Scale, error::QifError};
+
+/// A transaction as found in a bank statement, before it is mapped to a journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTxn {
+    pub date: Date<Utc>,
+    /// Amount in minor units of [Scale::CENTS], negative amounts are money leaving the account
+    pub amount: i64,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// Parse the transactions of a QIF (Quicken Interchange Format) statement
+///
+/// Every record is ended by a line with `^`, a last record without it is still
+/// read. Header lines starting with `!` and fields other than date (`D`),
+/// amount (`T` or `U`), payee (`P`) and memo (`M`) are skipped.
+///
+/// Dates may be written as `MM/DD/YYYY`, `MM/DD/YY`, `MM/DD'YY` or `YYYY-MM-DD`.
+/// A two digit year after `/` is in the 1900s and after `'` in the 2000s.
+///
+/// # Examples
+/// ```
+/// use chrono::prelude::*;
+/// use personal_finance::import::parse_qif;
+///
+/// let statement = "!Type:Bank\nD03/14'22\nT-1,250.50\nPGrocery store\n^\n";
+///
+/// let transactions = parse_qif(statement.as_bytes()).unwrap();
+/// assert_eq!(transactions[0].date, Utc.ymd(2022, 3, 14));
+/// assert_eq!(transactions[0].amount, -125050);
+/// ```
+pub fn parse_qif<R: BufRead>(reader: R) -> Result<Vec<RawTxn>, QifError> {
+    let mut transactions = Vec::new();
+    let mut record = Record::default();
+    let mut line_number = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+        line_number += 1;
+        let line = line.trim_end();
+
+        let mut chars = line.chars();
+        let value = chars.as_str().get(1..).unwrap_or_default().trim();
+        match chars.next() {
+            Some('^') => {
+                if let Some(transaction) = record.finish(line_number)? {
+                    transactions.push(transaction);
+                }
+                record = Record::default();
+            }
+            Some('D') => {
+                record.date = Some(parse_date(value).ok_or_else(|| QifError::InvalidDate {
+                    line: line_number,
+                    value: value.to_owned(),
+                })?);
+            }
+            Some('T' | 'U') => {
+                record.amount =
+                    Some(parse_amount(value).ok_or_else(|| QifError::InvalidAmount {
+                        line: line_number,
+                        value: value.to_owned(),
+                    })?);
+            }
+            Some('P') => record.payee = Some(value.to_owned()),
+            Some('M') => record.memo = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+
+    if let Some(transaction) = record.finish(line_number)? {
+        transactions.push(transaction);
+    }
+
+    Ok(transactions)
+}
+
+#[derive(Debug, Default)]
+struct Record {
+    date: Option<Date<Utc>>,
+    amount: Option<i64>,
+    payee: Option<String>,
+    memo: Option<String>,
+}
+
+impl Record {
+    fn is_empty(&self) -> bool {
+        self.date.is_none() && self.amount.is_none() && self.payee.is_none() && self.memo.is_none()
+    }
+
+    /// `line` is the line ending the record, used when reporting missing fields
+    fn finish(self, line: usize) -> Result<Option<RawTxn>, QifError> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(RawTxn {
+            date: self.date.ok_or(QifError::MissingDate { line })?,
+            amount: self.amount.ok_or(QifError::MissingAmount { line })?,
+            payee: self.payee,
+            memo: self.memo,
+        }))
+    }
+}
+
+fn parse_date(value: &str) -> Option<Date<Utc>> {
+    let value = value.replace(' ', "");
+
+    if let Some((year, rest)) = value.split_once('-') {
+        let (month, day) = rest.split_once('-')?;
+        return Utc
+            .ymd_opt(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+            .single();
+    }
+
+    let (month, rest) = value.split_once('/')?;
+    let (day, year, century) = match rest.split_once('\'') {
+        Some((day, year)) => (day, year, 2000),
+        None => {
+            let (day, year) = rest.split_once('/')?;
+            (day, year, 1900)
+        }
+    };
+    let year = match year.parse::<i32>().ok()? {
+        year if year < 100 => century + year,
+        year => year,
+    };
+
+    Utc.ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+        .single()
+}
+
+fn parse_amount(value: &str) -> Option<i64> {
+    Scale::CENTS.parse(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use test_case::test_case;
+
+    const STATEMENT: &str = "!Type:Bank
+D03/01/2022
+T2,500.00
+PEmployer Inc
+MSalary
+^
+D 3/ 4'22
+T-45.5
+PGrocery store
+^
+D2022-03-07
+U-1200
+PLandlord
+NCheck 1001
+^
+";
+
+    #[test]
+    fn parse_statement_with_several_records() {
+        let actual = parse_qif(STATEMENT.as_bytes()).unwrap();
+
+        assert_eq!(
+            actual,
+            vec![
+                RawTxn {
+                    date: Utc.ymd(2022, 3, 1),
+                    amount: 250000,
+                    payee: Some(String::from("Employer Inc")),
+                    memo: Some(String::from("Salary")),
+                },
+                RawTxn {
+                    date: Utc.ymd(2022, 3, 4),
+                    amount: -4550,
+                    payee: Some(String::from("Grocery store")),
+                    memo: None,
+                },
+                RawTxn {
+                    date: Utc.ymd(2022, 3, 7),
+                    amount: -120000,
+                    payee: Some(String::from("Landlord")),
+                    memo: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn last_record_without_separator_is_read() {
+        let actual = parse_qif("D01/02/2022\nT10".as_bytes()).unwrap();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].amount, 1000);
+    }
+
+    #[test]
+    fn record_without_amount_is_an_error() {
+        let actual = parse_qif("!Type:Bank\nD01/02/2022\nPShop\n^\n".as_bytes());
+
+        assert!(matches!(actual, Err(QifError::MissingAmount { line: 4 })));
+    }
+
+    #[test]
+    fn invalid_date_reports_line() {
+        let actual = parse_qif("!Type:Bank\nD13/45/2022\nT10\n^\n".as_bytes());
+
+        assert!(matches!(actual, Err(QifError::InvalidDate { line: 2, .. })));
+    }
+
+    #[test_case("03/14/2022" => Some(Utc.ymd(2022, 3, 14)) ; "four digit year")]
+    #[test_case("3/14/98" => Some(Utc.ymd(1998, 3, 14)) ; "two digit year with slash")]
+    #[test_case("3/14'05" => Some(Utc.ymd(2005, 3, 14)) ; "two digit year with apostrophe")]
+    #[test_case(" 3/ 4' 5" => Some(Utc.ymd(2005, 3, 4)) ; "space padded")]
+    #[test_case("2022-03-14" => Some(Utc.ymd(2022, 3, 14)) ; "iso")]
+    #[test_case("14.03.2022" => None ; "unsupported")]
+    fn qif_date(value: &str) -> Option<Date<Utc>> {
+        parse_date(value)
+    }
+
+    #[test_case("1,234.56" => Some(123456))]
+    #[test_case("-0.5" => Some(-50))]
+    #[test_case("12" => Some(1200))]
+    #[test_case("1.234" => None)]
+    #[test_case("abc" => None)]
+    #[test_case("-" => None)]
+    fn qif_amount(value: &str) -> Option<i64> {
+        parse_amount(value)
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
+
 use chrono::prelude::*;
 
-use crate::balance::Balance;
+use crate::balance::{Balance, NetBalance};
 use crate::entry::{Account, ValidatedJournal};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,12 +26,12 @@ impl<'a> Ledger<'a> {
     }
 
     /// Push an entry in the ledger only if the entry is for
-    /// the same account
+    /// the same account, see [Account::same_account]
     pub fn push(&mut self, journal: ValidatedJournal) -> usize {
         let mut count = 0;
         let date = journal.date().to_owned();
         for entry in journal {
-            if entry.account() == self.account {
+            if entry.account().same_account(self.account) {
                 let ledger_entry = LedgerEntry {
                     date,
                     transaction: entry.transaction,
@@ -46,6 +48,87 @@ impl<'a> Ledger<'a> {
     pub fn iter(&self) -> Iter<'_> {
         Iter::new(&self.entries)
     }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry that was added last
+    pub fn last(&self) -> Option<(&Date<Utc>, &Balance)> {
+        self.entries
+            .last()
+            .map(|entry| (&entry.date, &entry.transaction))
+    }
+
+    /// Sum of all debit entries in the ledger
+    pub fn total_debits(&self) -> u64 {
+        self.iter()
+            .filter_map(|(_, balance)| match balance {
+                Balance::Debit(x) => Some(u64::from(x.amount())),
+                Balance::Credit(_) => None,
+            })
+            .sum()
+    }
+
+    /// Sum of all credit entries in the ledger
+    pub fn total_credits(&self) -> u64 {
+        self.iter()
+            .filter_map(|(_, balance)| match balance {
+                Balance::Credit(x) => Some(u64::from(x.amount())),
+                Balance::Debit(_) => None,
+            })
+            .sum()
+    }
+
+    /// The signed total of the ledger where debits are positive and credits are negative
+    pub fn net(&self) -> i64 {
+        self.iter()
+            .map(|(_, balance)| *balance)
+            .sum::<NetBalance>()
+            .value()
+    }
+
+    /// The balance of the account after each entry in the order they were added
+    ///
+    /// Debits are positive and credits are negative, the same as [Ledger::net].
+    pub fn running_balances(&self) -> impl Iterator<Item = (Date<Utc>, i64)> + '_ {
+        self.iter()
+            .scan(NetBalance::ZERO, |total, (date, balance)| {
+                *total += *balance;
+                Some((*date, total.value()))
+            })
+    }
+
+    /// Net movement of the ledger per month keyed by (year, month)
+    ///
+    /// Debits are positive and credits are negative, the same as [Ledger::net].
+    pub fn by_month(&self) -> BTreeMap<(i32, u32), i64> {
+        self.iter()
+            .fold(BTreeMap::new(), |mut months, (date, balance)| {
+                *months
+                    .entry((date.year(), date.month()))
+                    .or_insert(NetBalance::ZERO) += *balance;
+                months
+            })
+            .into_iter()
+            .map(|(month, net)| (month, net.value()))
+            .collect()
+    }
+}
+
+/// Add entries without checking the account, the caller must make sure all
+/// entries belong to the account of the ledger
+impl Extend<(Date<Utc>, Balance)> for Ledger<'_> {
+    fn extend<T: IntoIterator<Item = (Date<Utc>, Balance)>>(&mut self, iter: T) {
+        self.entries.extend(
+            iter.into_iter()
+                .map(|(date, transaction)| LedgerEntry { date, transaction }),
+        );
+    }
 }
 
 pub struct Iter<'a> {
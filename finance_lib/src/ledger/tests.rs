@@ -4,8 +4,8 @@ use super::*;
 
 use crate::{
     account::{self, Category},
-    balance::Balance,
-    entry::Account,
+    balance::{Balance, Transaction},
+    entry::{Account, Journal},
 };
 
 #[test]
@@ -17,7 +17,7 @@ fn ledger_iter() {
     );
     let mut ledger = Ledger::new(&account);
 
-    let transactions = vec![
+    let transactions = [
         Balance::debit(150).unwrap(),
         Balance::debit(270).unwrap(),
         Balance::credit(50).unwrap(),
@@ -41,3 +41,151 @@ fn ledger_iter() {
 
     assert_eq!(actual, expected);
 }
+
+fn mixed_ledger(account: &Account) -> Ledger<'_> {
+    let mut ledger = Ledger::new(account);
+    let entries = [
+        (Utc.ymd(2021, 2, 10), Balance::debit(150).unwrap()),
+        (Utc.ymd(2021, 2, 15), Balance::debit(270).unwrap()),
+        (Utc.ymd(2021, 3, 5), Balance::credit(50).unwrap()),
+        (Utc.ymd(2021, 3, 8), Balance::credit(500).unwrap()),
+    ];
+
+    for (date, transaction) in entries {
+        ledger.entries.push(LedgerEntry { date, transaction });
+    }
+
+    ledger
+}
+
+#[test]
+fn ledger_aggregates() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("test").unwrap(),
+        Category::Asset,
+    );
+    let ledger = mixed_ledger(&account);
+
+    assert_eq!(ledger.total_debits(), 420);
+    assert_eq!(ledger.total_credits(), 550);
+    assert_eq!(ledger.net(), -130);
+}
+
+#[test]
+fn ledger_aggregates_empty() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("test").unwrap(),
+        Category::Asset,
+    );
+    let ledger = Ledger::new(&account);
+
+    assert_eq!(ledger.total_debits(), 0);
+    assert_eq!(ledger.total_credits(), 0);
+    assert_eq!(ledger.net(), 0);
+}
+
+#[test]
+fn ledger_by_month() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("test").unwrap(),
+        Category::Asset,
+    );
+    let mut ledger = Ledger::new(&account);
+
+    let entries = [
+        (Utc.ymd(2021, 3, 5), Balance::credit(50).unwrap()),
+        (Utc.ymd(2021, 2, 10), Balance::debit(150).unwrap()),
+        (Utc.ymd(2021, 4, 1), Balance::debit(10).unwrap()),
+        (Utc.ymd(2021, 2, 15), Balance::debit(270).unwrap()),
+        (Utc.ymd(2021, 3, 20), Balance::debit(30).unwrap()),
+        (Utc.ymd(2021, 4, 30), Balance::credit(100).unwrap()),
+    ];
+
+    for (date, transaction) in entries {
+        ledger.entries.push(LedgerEntry { date, transaction });
+    }
+
+    let actual = ledger.by_month().into_iter().collect::<Vec<_>>();
+
+    let expected = vec![((2021, 2), 420), ((2021, 3), -20), ((2021, 4), -90)];
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn ledger_push_should_accept_renamed_account() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("Bank").unwrap(),
+        Category::Asset,
+    );
+    let renamed = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("Bank account").unwrap(),
+        Category::Asset,
+    );
+    let cash = Account::new(
+        account::Number::new(102).unwrap(),
+        account::Name::new("Cash").unwrap(),
+        Category::Asset,
+    );
+    let mut ledger = Ledger::new(&account);
+
+    let mut journal = Journal::new(Utc.ymd(2021, 2, 10));
+    journal.push(&renamed, Transaction::debit(50).unwrap());
+    journal.push(&cash, Transaction::credit(50).unwrap());
+
+    let count = ledger.push(journal.validate().unwrap());
+
+    assert_eq!(count, 1);
+    assert_eq!(ledger.total_debits(), 50);
+}
+
+#[test]
+fn ledger_len_and_last() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("test").unwrap(),
+        Category::Asset,
+    );
+    let mut ledger = Ledger::new(&account);
+    assert!(ledger.is_empty());
+    assert_eq!(ledger.last(), None);
+
+    ledger.extend([
+        (Utc.ymd(2021, 2, 10), Balance::debit(150).unwrap()),
+        (Utc.ymd(2021, 2, 15), Balance::debit(270).unwrap()),
+        (Utc.ymd(2021, 3, 5), Balance::credit(50).unwrap()),
+    ]);
+
+    assert!(!ledger.is_empty());
+    assert_eq!(ledger.len(), 3);
+    assert_eq!(
+        ledger.last(),
+        Some((&Utc.ymd(2021, 3, 5), &Balance::credit(50).unwrap()))
+    );
+}
+
+#[test]
+fn ledger_running_balances() {
+    let account = Account::new(
+        account::Number::new(101).unwrap(),
+        account::Name::new("test").unwrap(),
+        Category::Asset,
+    );
+    let ledger = mixed_ledger(&account);
+
+    let actual = ledger.running_balances().collect::<Vec<_>>();
+
+    let expected = vec![
+        (Utc.ymd(2021, 2, 10), 150),
+        (Utc.ymd(2021, 2, 15), 420),
+        (Utc.ymd(2021, 3, 5), 370),
+        (Utc.ymd(2021, 3, 8), -130),
+    ];
+
+    assert_eq!(actual, expected);
+}
@@ -4,4 +4,6 @@ pub mod account;
 pub mod balance;
 pub mod entry;
 pub mod error;
+pub mod import;
 pub mod ledger;
+pub mod recurrence;
@@ -0,0 +1,182 @@
+use chrono::{prelude::*, Duration};
+
+use crate::entry::Journal;
+
+/// How often a recurring entry occurs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+/// A schedule of dates for entries that repeat, like rent or subscriptions
+///
+/// Month based intervals are always counted from the start date and clamped to
+/// the end of the month, so a recurrence starting on January 31st falls on
+/// February 28th (or 29th) and then on March 31st.
+///
+/// # Examples
+/// ```
+/// use chrono::prelude::*;
+/// use personal_finance::recurrence::{Interval, Recurrence};
+///
+/// let rent = Recurrence::new(Utc.ymd(2021, 11, 30), Interval::Monthly, 3);
+///
+/// let dates = rent.dates().collect::<Vec<_>>();
+/// assert_eq!(dates, vec![Utc.ymd(2021, 11, 30), Utc.ymd(2021, 12, 30), Utc.ymd(2022, 1, 30)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recurrence {
+    start: Date<Utc>,
+    interval: Interval,
+    count: usize,
+}
+
+impl Recurrence {
+    pub fn new(start: Date<Utc>, interval: Interval, count: usize) -> Self {
+        Self {
+            start,
+            interval,
+            count,
+        }
+    }
+
+    /// Iterate over all dates in the schedule
+    pub fn dates(&self) -> impl Iterator<Item = Date<Utc>> {
+        let Self {
+            start,
+            interval,
+            count,
+        } = *self;
+
+        (0..count).map(move |n| {
+            let n = n as u32;
+            match interval {
+                Interval::Daily => start + Duration::days(i64::from(n)),
+                Interval::Weekly => start + Duration::weeks(i64::from(n)),
+                Interval::Monthly => add_months(start, n),
+                Interval::Quarterly => add_months(start, n * 3),
+                Interval::Yearly => add_months(start, n * 12),
+            }
+        })
+    }
+
+    /// Create a copy of the template journal for each date in the schedule
+    pub fn journals<'a>(&self, template: &Journal<'a>) -> Vec<Journal<'a>> {
+        self.dates()
+            .map(|date| {
+                let mut journal = template.clone();
+                journal.set_date(date);
+                journal
+            })
+            .collect()
+    }
+}
+
+fn add_months(date: Date<Utc>, months: u32) -> Date<Utc> {
+    let months = date.month0() + months;
+    let year = date.year() + (months / 12) as i32;
+    let month = months % 12 + 1;
+    let day = date.day().min(days_in_month(year, month));
+
+    Utc.ymd(year, month, day)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (year, month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+
+    NaiveDate::from_ymd(year, month, 1).pred().day()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        account::{Category, Name, Number},
+        balance::Transaction,
+        entry::Account,
+    };
+
+    #[test]
+    fn monthly_recurrence_crossing_year_boundary() {
+        let recurrence = Recurrence::new(Utc.ymd(2021, 11, 15), Interval::Monthly, 4);
+
+        let actual = recurrence.dates().collect::<Vec<_>>();
+
+        let expected = vec![
+            Utc.ymd(2021, 11, 15),
+            Utc.ymd(2021, 12, 15),
+            Utc.ymd(2022, 1, 15),
+            Utc.ymd(2022, 2, 15),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn monthly_recurrence_clamps_to_month_end() {
+        let recurrence = Recurrence::new(Utc.ymd(2021, 1, 31), Interval::Monthly, 4);
+
+        let actual = recurrence.dates().collect::<Vec<_>>();
+
+        let expected = vec![
+            Utc.ymd(2021, 1, 31),
+            Utc.ymd(2021, 2, 28),
+            Utc.ymd(2021, 3, 31),
+            Utc.ymd(2021, 4, 30),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn weekly_recurrence() {
+        let recurrence = Recurrence::new(Utc.ymd(2021, 12, 24), Interval::Weekly, 3);
+
+        let actual = recurrence.dates().collect::<Vec<_>>();
+
+        let expected = vec![
+            Utc.ymd(2021, 12, 24),
+            Utc.ymd(2021, 12, 31),
+            Utc.ymd(2022, 1, 7),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn journals_are_copied_onto_each_date() {
+        let bank = Account::new(
+            Number::new(101).unwrap(),
+            Name::new("Bank account").unwrap(),
+            Category::Asset,
+        );
+        let rent = Account::new(
+            Number::new(502).unwrap(),
+            Name::new("Rent").unwrap(),
+            Category::Expenses,
+        );
+
+        let mut template = Journal::new(Utc.ymd(2021, 1, 1));
+        template.set_description("Rent");
+        template.push(&bank, Transaction::credit(500).unwrap());
+        template.push(&rent, Transaction::debit(500).unwrap());
+
+        let recurrence = Recurrence::new(Utc.ymd(2021, 1, 31), Interval::Monthly, 2);
+        let journals = recurrence.journals(&template);
+
+        assert_eq!(
+            journals.iter().map(|x| *x.date()).collect::<Vec<_>>(),
+            vec![Utc.ymd(2021, 1, 31), Utc.ymd(2021, 2, 28)]
+        );
+        assert!(journals
+            .iter()
+            .all(|x| x.as_slice() == template.as_slice()
+                && x.description() == template.description()));
+    }
+}
@@ -1,22 +1,28 @@
-use std::{ops::Deref, sync::Arc};
+use std::collections::BTreeMap;
 
 use async_trait::async_trait;
 use chrono::prelude::*;
-use futures::future::OptionFuture;
 
-use crate::{message::Responder, Message, MessageProcessor};
+use crate::{
+    message::{ProgressResponder, Responder},
+    Message, MessageProcessor, Progress,
+};
 use cqrs::{
-    error::{AccountError, LedgerError, TransactionError},
+    error::{AccountError, LedgerError, StoreError, TransactionError},
     events::{store::EventStorage, EventPointer, EventPointerType},
-    write::ledger::LedgerId,
-    Balance,
-    Event,
-    JournalId,
+    write::ledger::{FuturePolicy, LedgerId, WriteOutcome},
+    Event, Posting,
+};
+use personal_finance::{
+    account::{Category, Name, Number},
+    balance::Balance,
 };
-use personal_finance::account::{Category, Name, Number};
 
 pub struct CommandHandler<T> {
     store_handle: T,
+    dropped_replies: usize,
+    record_rejections: bool,
+    allow_future_dates: bool,
 }
 
 impl<T> CommandHandler<T>
@@ -24,25 +30,109 @@ where
     T: EventStorage<Event>,
 {
     pub fn new(store_handle: T) -> Self {
-        Self { store_handle }
+        Self {
+            store_handle,
+            dropped_replies: 0,
+            record_rejections: false,
+            allow_future_dates: false,
+        }
+    }
+
+    /// Store an [Event::CommandRejected] when opening an account or posting a
+    /// transaction fails, this is off by default
+    pub fn set_record_rejections(&mut self, enabled: bool) {
+        self.record_rejections = enabled;
+    }
+
+    /// Accept transactions dated any time in the future, for example scheduled entries
+    ///
+    /// This is off by default and transactions dated more than
+    /// [FUTURE_GRACE_DAYS](cqrs::write::ledger::FUTURE_GRACE_DAYS) after today are rejected.
+    pub fn set_allow_future_dates(&mut self, enabled: bool) {
+        self.allow_future_dates = enabled;
+    }
+
+    /// The policy for future dated transactions as of today
+    fn future_policy(&self) -> FuturePolicy {
+        if self.allow_future_dates {
+            FuturePolicy::Allow
+        } else {
+            FuturePolicy::from_today(Utc::now().date())
+        }
+    }
+
+    /// Number of replies that couldn't be sent because the receiver was dropped
+    ///
+    /// Events of a command are stored before the reply is sent, so a dropped
+    /// reply doesn't mean the command failed.
+    pub fn dropped_replies(&self) -> usize {
+        self.dropped_replies
+    }
+
+    /// The store the handler writes events to
+    ///
+    /// To run several handlers over one store give each a
+    /// [StoreProxy](cqrs::events::store::StoreProxy).
+    pub fn store(&self) -> &T {
+        &self.store_handle
     }
 }
 
-impl<'a, T> CommandHandler<T>
+impl<T> CommandHandler<T>
 where
-    T: EventStorage<Event> + Extend<EventPointerType>,
+    T: EventStorage<Event> + Send,
 {
-    async fn send_reply<U, E>(&mut self, reply_channel: Responder<U, E>, reply: Result<U, E>) {
-        OptionFuture::from(reply_channel.map(|rc| async { rc.send(reply) })).await;
+    async fn send_reply<U, E>(&mut self, reply_channel: Responder<U, E>, reply: Result<U, E>)
+    where
+        E: std::fmt::Debug,
+    {
+        #[cfg(feature = "tracing")]
+        match &reply {
+            Ok(_) => tracing::info!("command succeeded"),
+            Err(error) => tracing::warn!(?error, "command failed"),
+        }
+
+        if let Some(reply_channel) = reply_channel {
+            if reply_channel.send(reply).is_err() {
+                self.dropped_replies += 1;
+
+                #[cfg(feature = "tracing")]
+                tracing::debug!("reply receiver was dropped");
+            }
+        }
+    }
+
+    /// Store an [Event::CommandRejected] if enabled and the command failed with `reason`
+    async fn record_rejection(&mut self, ledger: &LedgerId, reason: Option<String>) {
+        if let (true, Some(reason)) = (self.record_rejections, reason) {
+            let rejected = Event::CommandRejected {
+                ledger: ledger.clone(),
+                reason,
+            };
+            let _ = self.store_handle.append_all_async(&[rejected]).await;
+        }
+    }
+
+    /// Store all events of a successful command or none of them
+    ///
+    /// Returns the number of stored events.
+    async fn commit<E>(&mut self, events: Result<Vec<Event>, E>) -> Result<usize, E>
+    where
+        E: From<StoreError>,
+    {
+        let events = events?;
+        self.store_handle.append_all_async(&events).await?;
+        Ok(events.len())
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
     async fn process_create_account_message(
         &mut self,
         ledger: LedgerId,
         id: Number,
         description: Name,
         category: Category,
-        reply_channel: Responder<(), AccountError>,
+        reply_channel: Responder<WriteOutcome, AccountError>,
     ) {
         let events = self
             .store_handle
@@ -51,30 +141,26 @@ where
             .cloned()
             .map(Event::new)
             .collect::<Vec<_>>();
-        let entry = cqrs::Ledger::new(ledger, events.as_slice())
+        let entry = cqrs::Ledger::new(ledger.clone(), events.as_slice())
             .ok_or(AccountError::LedgerDoesnExist)
-            .and_then(|mut ledger| {
-                ledger
-                    .open_account(id, description, category)
-                    .map(|events| {
-                        self.store_handle.extend(
-                            events
-                                .iter()
-                                .map(<Event as EventPointer>::Pointer::<Event>::clone),
-                        )
-                    })
-            });
+            .and_then(|mut ledger| ledger.open_account(id, description, category).map(owned));
+        let entry = self.commit(entry).await;
+        self.record_rejection(&ledger, entry.as_ref().err().map(ToString::to_string))
+            .await;
+        let entry = entry.map(|events| WriteOutcome { events, ledger });
 
         self.send_reply(reply_channel, entry).await;
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
     async fn process_transaction_message(
         &mut self,
         ledger: LedgerId,
         description: String,
-        transactions: Vec<(Number, Balance)>,
+        transactions: Vec<Posting>,
         date: Date<Utc>,
-        reply_channel: Responder<(), TransactionError>,
+        metadata: BTreeMap<String, String>,
+        reply_channel: Responder<WriteOutcome, TransactionError>,
     ) {
         let events = self
             .store_handle
@@ -83,69 +169,165 @@ where
             .cloned()
             .map(Event::new)
             .collect::<Vec<_>>();
-        let entry = cqrs::Ledger::new(ledger, &events)
+        let future_policy = self.future_policy();
+        let entry = cqrs::Ledger::new(ledger.clone(), &events)
             .ok_or(TransactionError::LedgerDoesnExist)
             .and_then(|mut ledger| {
+                ledger.set_future_policy(future_policy);
                 ledger
-                    .transaction(description, &transactions, date)
-                    .map(|events| {
-                        self.store_handle.extend(
-                            events
-                                .iter()
-                                .map(<Event as EventPointer>::Pointer::<Event>::clone),
-                        )
-                    })
+                    .transaction_with_metadata(description, transactions.into(), date, metadata)
+                    .map(owned)
             });
+        let entry = self.commit(entry).await;
+        self.record_rejection(&ledger, entry.as_ref().err().map(ToString::to_string))
+            .await;
+        let entry = entry.map(|events| WriteOutcome { events, ledger });
 
         self.send_reply(reply_channel, entry).await;
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
+    async fn process_post_day_book(
+        &mut self,
+        ledger: LedgerId,
+        entries: Vec<(String, Vec<Posting>, Date<Utc>)>,
+        progress: ProgressResponder<Progress>,
+        reply_channel: Responder<usize, TransactionError>,
+    ) {
+        let events = self
+            .store_handle
+            .all()
+            .iter()
+            .cloned()
+            .map(Event::new)
+            .collect::<Vec<_>>();
+
+        let reply = match cqrs::Ledger::new(ledger.clone(), &events) {
+            None => Err(TransactionError::LedgerDoesnExist),
+            Some(mut ledger) => {
+                ledger.set_future_policy(self.future_policy());
+                let total = entries.len();
+                let mut reply = Ok(0);
+                for (index, (description, transactions, date)) in entries.into_iter().enumerate() {
+                    let events = ledger
+                        .transaction(description, transactions.into(), date)
+                        .map(owned);
+                    let committed = self.commit(events).await;
+                    if let Err(e) = committed {
+                        reply = Err(e);
+                        break;
+                    }
+
+                    reply = Ok(index + 1);
+                    if let Some(progress) = &progress {
+                        // Waiting for room could deadlock a caller that reads the reply first
+                        let _ = progress.try_send(Progress {
+                            completed: index + 1,
+                            total,
+                        });
+                    }
+                }
+
+                reply
+            }
+        };
+        self.record_rejection(&ledger, reply.as_ref().err().map(ToString::to_string))
+            .await;
+
+        self.send_reply(reply_channel, reply).await;
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
+    async fn process_transaction_batch(
+        &mut self,
+        ledger: LedgerId,
+        entries: Vec<(String, Vec<Posting>, Date<Utc>)>,
+        reply_channel: Responder<WriteOutcome, TransactionError>,
+    ) {
+        let events = self
+            .store_handle
+            .all()
+            .iter()
+            .cloned()
+            .map(Event::new)
+            .collect::<Vec<_>>();
+        let future_policy = self.future_policy();
+        let batch = cqrs::Ledger::new(ledger.clone(), &events)
+            .ok_or(TransactionError::LedgerDoesnExist)
+            .and_then(|mut ledger| {
+                ledger.set_future_policy(future_policy);
+                entries.into_iter().try_fold(
+                    Vec::new(),
+                    |mut batch, (description, transactions, date)| {
+                        let events = ledger.transaction(description, transactions.into(), date)?;
+                        batch.extend(owned(events));
+                        Ok(batch)
+                    },
+                )
+            });
+        let reply = self.commit(batch).await;
+        self.record_rejection(&ledger, reply.as_ref().err().map(ToString::to_string))
+            .await;
+        let reply = reply.map(|events| WriteOutcome { events, ledger });
+
+        self.send_reply(reply_channel, reply).await;
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
+    async fn process_account_ledger(
+        &mut self,
+        ledger: LedgerId,
+        account: Number,
+        reply_channel: Responder<Vec<(Date<Utc>, Balance, String)>, AccountError>,
+    ) {
+        let events = self.store_handle.all();
+        let events = events.iter().cloned().map(Event::new).collect::<Vec<_>>();
+        let reply = cqrs::Ledger::new(ledger, events.as_slice())
+            .ok_or(AccountError::LedgerDoesnExist)
+            .and_then(|ledger| ledger.account_lines(account));
+
+        self.send_reply(reply_channel, reply).await;
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?ledger)))]
     async fn process_close_account(
         &mut self,
         ledger: LedgerId,
         id: Number,
-        reply_channel: Responder<(), AccountError>,
+        reply_channel: Responder<WriteOutcome, AccountError>,
     ) {
         let events = self.store_handle.all();
         let events = events.iter().cloned().map(Event::new).collect::<Vec<_>>();
-        let reply = cqrs::Ledger::new(ledger, events.as_slice())
+        let reply = cqrs::Ledger::new(ledger.clone(), events.as_slice())
             .ok_or(AccountError::LedgerDoesnExist)
-            .and_then(|mut ledger| {
-                ledger.close_account(id).map(|events| {
-                    self.store_handle.extend(
-                        events
-                            .iter()
-                            .map(<Event as EventPointer>::Pointer::<Event>::clone),
-                    )
-                })
-            });
+            .and_then(|mut ledger| ledger.close_account(id).map(owned));
+        let reply = self
+            .commit(reply)
+            .await
+            .map(|events| WriteOutcome { events, ledger });
 
         self.send_reply(reply_channel, reply).await;
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(ledger = ?id)))]
     async fn process_create_ledger(
         &mut self,
         id: LedgerId,
-        reply_channel: Responder<(), LedgerError>,
+        reply_channel: Responder<WriteOutcome, LedgerError>,
     ) {
         let events = self.store_handle.all();
-        let mut resolver = cqrs::write::ledger::LedgerResolver::new(&events);
+        let mut resolver = cqrs::write::ledger::LedgerResolver::new(events);
 
-        let reply = resolver.create(id).map(|events| {
-            self.store_handle
-                .extend(events.iter().cloned().map(Event::new));
-        });
+        let reply = resolver.create(id.clone()).map(<[Event]>::to_vec);
+        let reply = self
+            .commit(reply)
+            .await
+            .map(|events| WriteOutcome { events, ledger: id });
 
         self.send_reply(reply_channel, reply).await;
     }
-}
 
-#[async_trait]
-impl<T> MessageProcessor<Message> for CommandHandler<T>
-where
-    T: EventStorage<Event> + Extend<EventPointerType> + Send,
-{
-    async fn process_message(&mut self, message: Message) {
+    async fn dispatch(&mut self, message: Message) {
         match message {
             Message::CreateAccount {
                 ledger,
@@ -168,6 +350,7 @@ where
                 description,
                 transactions,
                 date,
+                metadata,
                 reply_channel,
             } => {
                 self.process_transaction_message(
@@ -175,10 +358,36 @@ where
                     description,
                     transactions,
                     date,
+                    metadata,
                     reply_channel,
                 )
                 .await
             }
+            Message::PostDayBook {
+                ledger,
+                entries,
+                progress,
+                reply_channel,
+            } => {
+                self.process_post_day_book(ledger, entries, progress, reply_channel)
+                    .await
+            }
+            Message::TransactionBatch {
+                ledger,
+                entries,
+                reply_channel,
+            } => {
+                self.process_transaction_batch(ledger, entries, reply_channel)
+                    .await
+            }
+            Message::AccountLedger {
+                ledger,
+                account,
+                reply_channel,
+            } => {
+                self.process_account_ledger(ledger, account, reply_channel)
+                    .await
+            }
             Message::CloseAccount {
                 ledger,
                 id,
@@ -190,3 +399,390 @@ where
         }
     }
 }
+
+fn owned(events: &[EventPointerType]) -> Vec<Event> {
+    events.iter().map(|x| Event::clone(x)).collect()
+}
+
+#[async_trait]
+impl<T> MessageProcessor<Message> for CommandHandler<T>
+where
+    T: EventStorage<Event> + Send,
+{
+    async fn process_message(&mut self, message: Message) {
+        // A store that can't be refreshed can't be written to either, the
+        // command reports that when its events are committed
+        let _ = self.store_handle.refresh_async().await;
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "process_message",
+            command = message.name(),
+            ledger = ?message.ledger(),
+            events = tracing::field::Empty,
+        );
+        #[cfg(feature = "tracing")]
+        let events_before = self.store_handle.all().len();
+
+        let process = async {
+            self.dispatch(message).await;
+        };
+
+        #[cfg(feature = "tracing")]
+        let process = tracing::Instrument::instrument(process, span.clone());
+
+        process.await;
+
+        #[cfg(feature = "tracing")]
+        span.record("events", self.store_handle.all().len() - events_before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cqrs::{events::store::InMemoryStore, Category};
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn store_should_show_events_of_processed_commands() {
+        let mut handler = CommandHandler::new(InMemoryStore::new());
+
+        handler
+            .process_message(Message::CreateLedger {
+                id: LedgerId::new("2014").unwrap(),
+                reply_channel: None,
+            })
+            .await;
+
+        assert_eq!(
+            handler.store().all(),
+            &[Event::LedgerCreated {
+                id: LedgerId::new("2014").unwrap()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn dropped_reply_should_keep_stored_events() {
+        let mut handler = CommandHandler::new(InMemoryStore::new());
+        let (tx, rx) = oneshot::channel();
+        drop(rx);
+
+        handler
+            .process_message(Message::CreateLedger {
+                id: LedgerId::new("2014").unwrap(),
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        assert_eq!(handler.dropped_replies(), 1);
+        assert_eq!(
+            handler.store_handle.all(),
+            &[Event::LedgerCreated {
+                id: LedgerId::new("2014").unwrap()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_transaction_should_be_recorded_when_enabled() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let mut handler = CommandHandler::new(InMemoryStore::new());
+        handler.set_record_rejections(true);
+        handler.store_handle.extend([
+            Event::LedgerCreated { id: ledger.clone() },
+            Event::account_opened(ledger.clone(), 101, "Bank", Category::Asset).unwrap(),
+            Event::account_opened(ledger.clone(), 501, "Groceries", Category::Expenses).unwrap(),
+        ]);
+        let (tx, rx) = oneshot::channel();
+
+        handler
+            .process_message(Message::Transaction {
+                ledger: ledger.clone(),
+                description: String::from("Unbalanced"),
+                transactions: vec![
+                    Posting::debit(Number::new(501).unwrap(), 100).unwrap(),
+                    Posting::credit(Number::new(101).unwrap(), 90).unwrap(),
+                ],
+                date: Utc.ymd(2014, 4, 1),
+                metadata: BTreeMap::new(),
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        let error = rx.await.unwrap().unwrap_err();
+        let events = handler.store_handle.all();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[3],
+            Event::CommandRejected {
+                ledger,
+                reason: error.to_string(),
+            }
+        );
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, Event::Transaction { .. })));
+    }
+
+    fn ledger_with_accounts(ledger: &LedgerId) -> CommandHandler<InMemoryStore<Event>> {
+        let mut handler = CommandHandler::new(InMemoryStore::new());
+        handler.store_handle.extend([
+            Event::LedgerCreated { id: ledger.clone() },
+            Event::account_opened(ledger.clone(), 101, "Bank", Category::Asset).unwrap(),
+            Event::account_opened(ledger.clone(), 501, "Groceries", Category::Expenses).unwrap(),
+        ]);
+
+        handler
+    }
+
+    fn groceries(amount: u32, date: Date<Utc>) -> (String, Vec<Posting>, Date<Utc>) {
+        (
+            String::from("Groceries"),
+            vec![
+                Posting::debit(Number::new(501).unwrap(), amount).unwrap(),
+                Posting::credit(Number::new(101).unwrap(), amount).unwrap(),
+            ],
+            date,
+        )
+    }
+
+    #[tokio::test]
+    async fn transaction_batch_should_store_all_transactions() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let mut handler = ledger_with_accounts(&ledger);
+        let (tx, rx) = oneshot::channel();
+
+        handler
+            .process_message(Message::TransactionBatch {
+                ledger: ledger.clone(),
+                entries: vec![
+                    groceries(100, Utc.ymd(2014, 4, 1)),
+                    groceries(50, Utc.ymd(2014, 4, 2)),
+                ],
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        assert_eq!(rx.await.unwrap(), Ok(WriteOutcome { events: 2, ledger }));
+        assert_eq!(handler.store().all().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn transaction_batch_with_a_bad_journal_should_store_nothing() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let mut handler = ledger_with_accounts(&ledger);
+        let before = handler.store().all().to_vec();
+        let (tx, rx) = oneshot::channel();
+
+        let mut missing_account = groceries(30, Utc.ymd(2014, 4, 3));
+        missing_account.1[0] = Posting::debit(Number::new(999).unwrap(), 30).unwrap();
+
+        handler
+            .process_message(Message::TransactionBatch {
+                ledger,
+                entries: vec![
+                    groceries(100, Utc.ymd(2014, 4, 1)),
+                    groceries(50, Utc.ymd(2014, 4, 2)),
+                    missing_account,
+                ],
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        assert!(rx.await.unwrap().is_err());
+        assert_eq!(handler.store().all(), before.as_slice());
+    }
+
+    async fn post_far_future(
+        handler: &mut CommandHandler<InMemoryStore<Event>>,
+    ) -> Result<usize, TransactionError> {
+        let (tx, rx) = oneshot::channel();
+        let date = Utc::now().date() + chrono::Duration::days(365);
+
+        handler
+            .process_message(Message::TransactionBatch {
+                ledger: LedgerId::new("2014").unwrap(),
+                entries: vec![groceries(100, date)],
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        rx.await.unwrap().map(|outcome| outcome.events)
+    }
+
+    #[tokio::test]
+    async fn far_future_transaction_should_be_rejected_by_default() {
+        let mut handler = ledger_with_accounts(&LedgerId::new("2014").unwrap());
+
+        assert_eq!(
+            post_far_future(&mut handler).await,
+            Err(TransactionError::FutureDated)
+        );
+    }
+
+    #[tokio::test]
+    async fn far_future_transaction_should_be_stored_when_allowed() {
+        let mut handler = ledger_with_accounts(&LedgerId::new("2014").unwrap());
+        handler.set_allow_future_dates(true);
+
+        assert_eq!(post_far_future(&mut handler).await, Ok(1));
+    }
+
+    /// A store that can only hold a few events
+    #[derive(Debug, Default)]
+    struct LimitedStore {
+        events: Vec<Event>,
+        capacity: usize,
+    }
+
+    impl EventStorage<Event> for LimitedStore {
+        fn append(&mut self, event: Event) -> usize {
+            self.events.push(event);
+            self.events.len() - 1
+        }
+
+        fn all(&self) -> &[Event] {
+            &self.events
+        }
+
+        fn append_all(&mut self, events: &[Event]) -> Result<(), StoreError> {
+            if self.events.len() + events.len() > self.capacity {
+                return Err(StoreError::Rejected(String::from("store is full")));
+            }
+
+            self.events.extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn failing_store_should_keep_none_of_the_command_events() {
+        let ledger = LedgerId::new("2014").unwrap();
+        let history = ledger_with_accounts(&ledger).store().all().to_vec();
+        let mut handler = CommandHandler::new(LimitedStore {
+            capacity: history.len() + 1,
+            events: history.clone(),
+        });
+        let (tx, rx) = oneshot::channel();
+
+        handler
+            .process_message(Message::TransactionBatch {
+                ledger,
+                entries: vec![
+                    groceries(100, Utc.ymd(2014, 4, 1)),
+                    groceries(50, Utc.ymd(2014, 4, 2)),
+                ],
+                reply_channel: Some(tx),
+            })
+            .await;
+
+        assert_eq!(
+            rx.await.unwrap(),
+            Err(TransactionError::Store(StoreError::Rejected(String::from(
+                "store is full"
+            ))))
+        );
+        assert_eq!(handler.store().all(), history.as_slice());
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use cqrs::events::store::InMemoryStore;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    struct CapturedSpan {
+        name: &'static str,
+        command: Option<String>,
+        events: Option<u64>,
+    }
+
+    impl Visit for CapturedSpan {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "command" {
+                self.command = Some(value.to_owned());
+            }
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "events" {
+                self.events = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    #[derive(Default, Clone)]
+    struct SpanCapture(Arc<Mutex<Vec<(Id, CapturedSpan)>>>);
+
+    impl<S: Subscriber> Layer<S> for SpanCapture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+            let mut span = CapturedSpan {
+                name: attrs.metadata().name(),
+                ..Default::default()
+            };
+            attrs.record(&mut span);
+            self.0.lock().unwrap().push((id.clone(), span));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            if let Some((_, span)) = self.0.lock().unwrap().iter_mut().find(|(x, _)| x == id) {
+                values.record(span);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn successful_command_should_emit_span() {
+        let capture = SpanCapture::default();
+        let _guard = tracing_subscriber::registry()
+            .with(capture.clone())
+            .set_default();
+
+        let mut handler = CommandHandler::new(InMemoryStore::new());
+        handler
+            .process_message(Message::CreateLedger {
+                id: LedgerId::new("2014").unwrap(),
+                reply_channel: None,
+            })
+            .await;
+
+        let spans = capture
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, span)| span.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            spans,
+            vec![
+                CapturedSpan {
+                    name: "process_message",
+                    command: Some(String::from("CreateLedger")),
+                    events: Some(1),
+                },
+                CapturedSpan {
+                    name: "process_create_ledger",
+                    command: None,
+                    events: None,
+                },
+            ]
+        );
+    }
+}
@@ -9,7 +9,7 @@ mod command_handler;
 mod message;
 
 pub use command_handler::CommandHandler;
-pub use message::Message;
+pub use message::{Message, Progress};
 
 #[derive(Debug)]
 pub enum MailboxProcessorError {
@@ -57,10 +57,59 @@ impl MailboxProcessor {
     }
 
     pub async fn post(&self, message: Message) -> Result<(), MailboxProcessorError> {
+        let name = message.name();
         self.sender
             .send(message)
             .await
             .into_report()
+            .attach_printable_lazy(|| format!("failed to post {name}"))
             .change_context(MailboxProcessorError::MailboxProcessTerminated)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cqrs::write::ledger::LedgerId;
+    use error_stack::{AttachmentKind, FrameKind};
+
+    struct PanickingProcessor;
+
+    #[async_trait]
+    #[allow(clippy::diverging_sub_expression)]
+    impl MessageProcessor<Message> for PanickingProcessor {
+        async fn process_message(&mut self, _message: Message) {
+            panic!("processor died");
+        }
+    }
+
+    fn create_ledger() -> Message {
+        Message::CreateLedger {
+            id: LedgerId::new("2014-q2").unwrap(),
+            reply_channel: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn post_to_terminated_mailbox_should_attach_message_name() {
+        let mb = MailboxProcessor::new(PanickingProcessor).await;
+        mb.post(create_ledger()).await.unwrap();
+        mb.sender.closed().await;
+
+        let report = mb.post(create_ledger()).await.unwrap_err();
+
+        let attachments = report
+            .frames()
+            .filter_map(|frame| match frame.kind() {
+                FrameKind::Attachment(AttachmentKind::Printable(x)) => Some(x.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            attachments,
+            vec![String::from("failed to post CreateLedger")]
+        );
+    }
+}
@@ -1,13 +1,32 @@
+use std::{collections::BTreeMap, fmt};
+
 use chrono::prelude::*;
 use tokio::sync;
 
-use cqrs::{write::ledger::LedgerId, JournalId};
+use cqrs::{
+    write::ledger::{LedgerId, WriteOutcome},
+    Posting,
+};
 use personal_finance::{
     account::{Category, Name, Number},
     balance::Balance,
 };
 
 pub type Responder<T, E> = Option<sync::oneshot::Sender<Result<T, E>>>;
+pub type ProgressResponder<T> = Option<sync::mpsc::Sender<T>>;
+
+/// Progress of a long running command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "posted {}/{}", self.completed, self.total)
+    }
+}
 
 #[derive(Debug)]
 pub enum Message {
@@ -16,22 +35,79 @@ pub enum Message {
         id: Number,
         description: Name,
         category: Category,
-        reply_channel: Responder<(), cqrs::error::AccountError>,
+        reply_channel: Responder<WriteOutcome, cqrs::error::AccountError>,
     },
     Transaction {
         ledger: LedgerId,
         description: String,
-        transactions: Vec<(Number, Balance)>,
+        transactions: Vec<Posting>,
         date: Date<Utc>,
-        reply_channel: Responder<(), cqrs::error::TransactionError>,
+        /// Free-form tags stored with the transaction, may be empty
+        metadata: BTreeMap<String, String>,
+        reply_channel: Responder<WriteOutcome, cqrs::error::TransactionError>,
+    },
+    /// Post several transactions in order, stopping at the first one that fails.
+    ///
+    /// Every transaction is stored on its own, so the transactions before a
+    /// failing one stay posted. Use [Message::TransactionBatch] to post all or
+    /// nothing. A progress update is sent after each posted transaction unless
+    /// the progress channel is full, and the reply holds the number of
+    /// transactions posted.
+    PostDayBook {
+        ledger: LedgerId,
+        entries: Vec<(String, Vec<Posting>, Date<Utc>)>,
+        progress: ProgressResponder<Progress>,
+        reply_channel: Responder<usize, cqrs::error::TransactionError>,
+    },
+    /// Post several transactions as one unit
+    ///
+    /// Either all transactions are stored or, if any of them fails, none are.
+    TransactionBatch {
+        ledger: LedgerId,
+        entries: Vec<(String, Vec<Posting>, Date<Utc>)>,
+        reply_channel: Responder<WriteOutcome, cqrs::error::TransactionError>,
+    },
+    /// All transaction lines of an account with their date and description
+    AccountLedger {
+        ledger: LedgerId,
+        account: Number,
+        reply_channel: Responder<Vec<(Date<Utc>, Balance, String)>, cqrs::error::AccountError>,
     },
     CloseAccount {
         ledger: LedgerId,
         id: Number,
-        reply_channel: Responder<(), cqrs::error::AccountError>,
+        reply_channel: Responder<WriteOutcome, cqrs::error::AccountError>,
     },
     CreateLedger {
         id: LedgerId,
-        reply_channel: Responder<(), cqrs::error::LedgerError>,
+        reply_channel: Responder<WriteOutcome, cqrs::error::LedgerError>,
     },
 }
+
+impl Message {
+    /// The name of the message variant, useful for describing the message in logs
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::CreateAccount { .. } => "CreateAccount",
+            Self::Transaction { .. } => "Transaction",
+            Self::PostDayBook { .. } => "PostDayBook",
+            Self::TransactionBatch { .. } => "TransactionBatch",
+            Self::AccountLedger { .. } => "AccountLedger",
+            Self::CloseAccount { .. } => "CloseAccount",
+            Self::CreateLedger { .. } => "CreateLedger",
+        }
+    }
+
+    /// The ledger the message is targeting
+    pub fn ledger(&self) -> &LedgerId {
+        match self {
+            Self::CreateAccount { ledger, .. }
+            | Self::Transaction { ledger, .. }
+            | Self::PostDayBook { ledger, .. }
+            | Self::TransactionBatch { ledger, .. }
+            | Self::AccountLedger { ledger, .. }
+            | Self::CloseAccount { ledger, .. } => ledger,
+            Self::CreateLedger { id, .. } => id,
+        }
+    }
+}
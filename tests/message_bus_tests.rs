@@ -1,12 +1,10 @@
-use std::{convert::TryInto, fmt::Debug};
-
 use chrono::prelude::*;
-use tokio::{sync, task};
+use tokio::sync;
 
 use cqrs::{
-    error::{AccountError, TransactionError},
-    events::store::InMemoryStore,
-    write::ledger::LedgerId,
+    error::{AccountError, BuildError, TransactionError},
+    events::store::{EventStorage, InMemoryStore, StoreProxy},
+    write::ledger::{LedgerId, WriteOutcome},
 };
 use message_bus::{CommandHandler, MailboxProcessor, Message};
 use personal_finance::{
@@ -36,10 +34,11 @@ macro_rules! message {
             description: String::from($desc),
             transactions: vec![
                 $(
-                    (Number::new($account).unwrap(), Balance::$ty($amount).unwrap()),
+                    (Number::new($account).unwrap(), Balance::$ty($amount).unwrap()).into(),
                 )*
             ],
             date: $date,
+            metadata: Default::default(),
             reply_channel: $rc,
         }
     };
@@ -73,8 +72,7 @@ async fn create_account() {
     let mb = default_mailbox().await;
     default_ledger(&mb).await;
 
-    let (message, mut rx) =
-        message_with_reply!(open, "2014-q2", 101, "Bank account", Category::Asset);
+    let (message, rx) = message_with_reply!(open, "2014-q2", 101, "Bank account", Category::Asset);
     let result = mb.post(message).await;
 
     let response = rx.await.unwrap();
@@ -88,8 +86,7 @@ async fn create_duplicate_account() {
     let mb = default_mailbox().await;
     default_ledger(&mb).await;
 
-    let (message, mut rx) =
-        message_with_reply!(open, "2014-q2", 101, "Bank account", Category::Asset);
+    let (message, rx) = message_with_reply!(open, "2014-q2", 101, "Bank account", Category::Asset);
     let result = mb.post(message).await;
 
     let response = rx.await.unwrap();
@@ -97,7 +94,7 @@ async fn create_duplicate_account() {
     assert!(result.is_ok());
     assert!(response.is_ok());
 
-    let (message, mut rx) =
+    let (message, rx) =
         message_with_reply!(open, "2014-q2", 101, "Duplicate account", Category::Asset);
     let result = mb.post(message).await;
 
@@ -161,7 +158,13 @@ async fn create_a_ledger_with_unique_id_should_succeed() {
     assert!(result.is_ok());
 
     let result = rx.await.unwrap();
-    assert_eq!(result, Ok(()));
+    assert_eq!(
+        result,
+        Ok(WriteOutcome {
+            events: 1,
+            ledger: LedgerId::new("2014-q2").unwrap()
+        })
+    );
 }
 
 #[tokio::test]
@@ -178,13 +181,42 @@ async fn creating_a_ledger_with_same_id_should_be_an_error() {
     assert_eq!(result, Err(cqrs::error::LedgerError::AlreadyExists));
 }
 
+#[test]
+fn blank_ledger_id_should_not_parse() {
+    assert_eq!(
+        LedgerId::parse("  "),
+        Err(BuildError::InvalidLedger(String::from("  ")))
+    );
+}
+
+#[tokio::test]
+async fn padded_ledger_id_should_resolve_to_the_same_ledger() -> Result<(), BuildError> {
+    let mb = default_mailbox().await;
+    let result = mb
+        .post(Message::CreateLedger {
+            id: LedgerId::parse(" 2014-q2 ")?,
+            reply_channel: None,
+        })
+        .await;
+    assert!(result.is_ok());
+
+    let (message, rx) = message_with_reply!(ledger, "2014-q2");
+    let result = mb.post(message).await;
+    assert!(result.is_ok());
+
+    let result = rx.await.unwrap();
+    assert_eq!(result, Err(cqrs::error::LedgerError::AlreadyExists));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn creating_several_transaction_should_be_ok() {
     let mb = default_mailbox().await;
     default_ledger(&mb).await;
     add_default_account(&mb).await;
 
-    let (message, mut rx) = message_with_reply!(entry, "2014-q2", "Grocery Shopping", Utc::now().date() => {
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Grocery Shopping", Utc::now().date() => {
         101 => credit 150,
         501 => debit 150,
     });
@@ -194,9 +226,9 @@ async fn creating_several_transaction_should_be_ok() {
 
     let result = rx.await.unwrap();
 
-    assert_eq!(result, Ok(()));
+    assert!(result.is_ok());
 
-    let (message, mut rx) = message_with_reply!(entry, "2014-q2", "Salary", Utc::now().date() => {
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Salary", Utc::now().date() => {
         101 => debit 10_000,
         401 => credit 10_000,
     });
@@ -205,7 +237,7 @@ async fn creating_several_transaction_should_be_ok() {
     assert!(result.is_ok());
 
     let result = rx.await.unwrap();
-    assert_eq!(result, Ok(()));
+    assert!(result.is_ok());
 }
 
 #[tokio::test]
@@ -214,7 +246,7 @@ async fn adding_a_transaction_to_a_non_existing_account_should_be_an_error() {
     add_default_account(&mb).await;
     default_ledger(&mb).await;
 
-    let (message, mut rx) = message_with_reply!(entry, "2014-q2", "Grocery shopping", Utc::now().date() => {
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Grocery shopping", Utc::now().date() => {
         101 => credit 150,
         601 => debit 150,
     });
@@ -232,7 +264,7 @@ async fn adding_no_transactions_to_an_entry_should_give_an_error() {
     add_default_account(&mb).await;
     default_ledger(&mb).await;
 
-    let (message, mut rx) = message_with_reply!(entry, "2014-q2", "Grocery shopping", Utc::now().date() => {
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Grocery shopping", Utc::now().date() => {
         // empty transactions
     });
     let result = mb.post(message).await;
@@ -249,15 +281,15 @@ async fn closing_an_account_twice_should_give_an_error() {
     default_ledger(&mb).await;
     add_default_account(&mb).await;
 
-    let (message, mut rx) = message_with_reply!(close, "2014-q2", 101);
+    let (message, rx) = message_with_reply!(close, "2014-q2", 101);
     let result = mb.post(message).await;
 
     assert!(result.is_ok());
 
     let response = rx.await.unwrap();
-    assert_eq!(response, Ok(()));
+    assert!(response.is_ok());
 
-    let (message, mut rx) = message_with_reply!(close, "2014-q2", 101);
+    let (message, rx) = message_with_reply!(close, "2014-q2", 101);
     let result = mb.post(message).await;
 
     assert!(result.is_ok());
@@ -271,8 +303,208 @@ async fn closing_a_non_existent_account_should_give_an_error() {
     let mb = default_mailbox().await;
     default_ledger(&mb).await;
 
-    let (message, mut rx) = message_with_reply!(close, "2014-q2", 101);
+    let (message, rx) = message_with_reply!(close, "2014-q2", 101);
     let result = mb.post(message).await;
+
+    assert!(result.is_ok());
+
     let response = rx.await.unwrap();
     assert_eq!(response, Err(AccountError::NotExist));
 }
+
+fn day_book(count: u32) -> Vec<(String, Vec<cqrs::Posting>, Date<Utc>)> {
+    (1..=count)
+        .map(|amount| {
+            (
+                String::from("Grocery shopping"),
+                vec![
+                    (Number::new(101).unwrap(), Balance::credit(amount).unwrap()).into(),
+                    (Number::new(501).unwrap(), Balance::debit(amount).unwrap()).into(),
+                ],
+                Utc::now().date(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn posting_a_day_book_should_report_progress() {
+    let mb = default_mailbox().await;
+    default_ledger(&mb).await;
+    add_default_account(&mb).await;
+
+    let entries = day_book(3);
+
+    let (progress_tx, mut progress_rx) = sync::mpsc::channel(10);
+    let (tx, rx) = sync::oneshot::channel();
+    let result = mb
+        .post(Message::PostDayBook {
+            ledger: LedgerId::new("2014-q2").unwrap(),
+            entries,
+            progress: Some(progress_tx),
+            reply_channel: Some(tx),
+        })
+        .await;
+
+    assert!(result.is_ok());
+
+    let response = rx.await.unwrap();
+    assert_eq!(response, Ok(3));
+
+    let mut progress = Vec::new();
+    while let Some(update) = progress_rx.recv().await {
+        progress.push(update.to_string());
+    }
+    assert_eq!(progress, vec!["posted 1/3", "posted 2/3", "posted 3/3"]);
+}
+
+#[tokio::test]
+async fn a_full_progress_channel_should_not_block_the_day_book() {
+    let mb = default_mailbox().await;
+    default_ledger(&mb).await;
+    add_default_account(&mb).await;
+
+    let (progress_tx, mut progress_rx) = sync::mpsc::channel(1);
+    let (tx, rx) = sync::oneshot::channel();
+    let result = mb
+        .post(Message::PostDayBook {
+            ledger: LedgerId::new("2014-q2").unwrap(),
+            entries: day_book(3),
+            progress: Some(progress_tx),
+            reply_channel: Some(tx),
+        })
+        .await;
+
+    assert!(result.is_ok());
+
+    let response = rx.await.unwrap();
+    assert_eq!(response, Ok(3));
+
+    let mut progress = Vec::new();
+    while let Some(update) = progress_rx.recv().await {
+        progress.push(update.to_string());
+    }
+    assert_eq!(progress, vec!["posted 1/3"]);
+}
+
+#[tokio::test]
+async fn a_transaction_should_report_one_event_for_its_ledger() {
+    let mb = default_mailbox().await;
+    default_ledger(&mb).await;
+    add_default_account(&mb).await;
+
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Grocery shopping", Utc::now().date() => {
+        101 => credit 150,
+        501 => debit 150,
+    });
+    let result = mb.post(message).await;
+
+    assert!(result.is_ok());
+
+    let response = rx.await.unwrap();
+    assert_eq!(
+        response,
+        Ok(WriteOutcome {
+            events: 1,
+            ledger: LedgerId::new("2014-q2").unwrap()
+        })
+    );
+}
+
+#[tokio::test]
+async fn account_ledger_should_list_all_lines_of_the_account() {
+    let mb = default_mailbox().await;
+    default_ledger(&mb).await;
+    add_default_account(&mb).await;
+
+    let _ = mb
+        .post(
+            message!(entry, "2014-q2", "Grocery shopping", Utc.ymd(2014, 4, 2) => {
+            101 => credit 150,
+            501 => debit 150,
+        }, None),
+        )
+        .await;
+    let _ = mb
+        .post(
+            message!(entry, "2014-q2", "Salary", Utc.ymd(2014, 4, 25) => {
+            101 => debit 10_000,
+            401 => credit 10_000,
+        }, None),
+        )
+        .await;
+
+    let (tx, rx) = sync::oneshot::channel();
+    let result = mb
+        .post(Message::AccountLedger {
+            ledger: LedgerId::new("2014-q2").unwrap(),
+            account: Number::new(101).unwrap(),
+            reply_channel: Some(tx),
+        })
+        .await;
+
+    assert!(result.is_ok());
+
+    let response = rx.await.unwrap();
+    assert_eq!(
+        response,
+        Ok(vec![
+            (
+                Utc.ymd(2014, 4, 2),
+                Balance::credit(150).unwrap(),
+                String::from("Grocery shopping")
+            ),
+            (
+                Utc.ymd(2014, 4, 25),
+                Balance::debit(10_000).unwrap(),
+                String::from("Salary")
+            ),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn account_ledger_of_unknown_account_should_be_an_error() {
+    let mb = default_mailbox().await;
+    default_ledger(&mb).await;
+
+    let (tx, rx) = sync::oneshot::channel();
+    let _ = mb
+        .post(Message::AccountLedger {
+            ledger: LedgerId::new("2014-q2").unwrap(),
+            account: Number::new(101).unwrap(),
+            reply_channel: Some(tx),
+        })
+        .await;
+
+    assert_eq!(rx.await.unwrap(), Err(AccountError::NotExist));
+}
+
+#[tokio::test]
+async fn handlers_sharing_a_store_proxy_should_see_each_others_events() {
+    let store = StoreProxy::spawn(InMemoryStore::new());
+    let mut observer = store.clone();
+    let first = MailboxProcessor::new(CommandHandler::new(store.clone())).await;
+    let second = MailboxProcessor::new(CommandHandler::new(store)).await;
+
+    let (message, rx) = message_with_reply!(ledger, "2014-q2");
+    first.post(message).await.unwrap();
+    assert!(rx.await.unwrap().is_ok());
+
+    let (message, rx) = message_with_reply!(open, "2014-q2", 101, "Bank account", Category::Asset);
+    second.post(message).await.unwrap();
+    assert!(rx.await.unwrap().is_ok());
+    let (message, rx) = message_with_reply!(open, "2014-q2", 501, "Groceries", Category::Expenses);
+    second.post(message).await.unwrap();
+    assert!(rx.await.unwrap().is_ok());
+
+    let (message, rx) = message_with_reply!(entry, "2014-q2", "Groceries", Utc.ymd(2014, 4, 2) => {
+        501 => debit 150,
+        101 => credit 150,
+    });
+    first.post(message).await.unwrap();
+    assert!(rx.await.unwrap().is_ok());
+
+    observer.refresh_async().await.unwrap();
+    assert_eq!(observer.all().len(), 4);
+}
@@ -1,9 +1,11 @@
+use std::collections::BTreeSet;
+
 use chrono::prelude::*;
 
 use personal_finance::{
     account::{Category, Name, Number},
     balance::Transaction,
-    entry::{Account, Journal},
+    entry::{Account, Journal, JournalLine},
 };
 
 pub fn accounts() -> Vec<Account> {
@@ -75,3 +77,261 @@ fn balanced_journal_should_be_invalid_given_non_zero_balance() {
 
     assert!(journal.is_err());
 }
+
+#[test]
+fn combined_journals_on_same_date_should_be_valid() {
+    let accounts = accounts();
+
+    let mut first = Journal::new(Utc.ymd(2005, 4, 23));
+    first.push(&accounts[1], Transaction::credit(50).unwrap());
+    first.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    let mut second = Journal::new(Utc.ymd(2005, 4, 23));
+    second.push(&accounts[0], Transaction::credit(20).unwrap());
+    second.push(&accounts[2], Transaction::debit(20).unwrap());
+
+    let journal = first.combine(second).unwrap();
+
+    assert_eq!(journal.as_slice().len(), 4);
+    assert!(journal.validate().is_ok());
+}
+
+#[test]
+fn combining_journals_on_different_dates_should_be_an_error() {
+    let accounts = accounts();
+
+    let mut first = Journal::new(Utc.ymd(2005, 4, 23));
+    first.push(&accounts[1], Transaction::credit(50).unwrap());
+    first.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    let second = Journal::new(Utc.ymd(2005, 4, 24));
+
+    let journal = first.combine(second);
+
+    assert!(journal.is_err());
+}
+
+#[test]
+fn balanced_journal_should_be_balanced() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    assert!(journal.is_balanced());
+    assert_eq!(journal.imbalance(), 0);
+}
+
+#[test]
+fn balanced_journal_should_be_balanced_given_split_transaction() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+    assert_eq!(journal.imbalance(), -40);
+
+    journal.push(&accounts[2], Transaction::debit(30).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+
+    assert!(journal.is_balanced());
+}
+
+#[test]
+fn journal_should_not_be_balanced_given_non_zero_balance() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(52).unwrap());
+
+    assert!(!journal.is_balanced());
+    assert_eq!(journal.imbalance(), 2);
+}
+
+#[test]
+fn split_transaction_journal_should_have_entries_on_two_accounts() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+    assert!(journal.is_empty());
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+    journal.push(&accounts[2], Transaction::debit(30).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+
+    assert_eq!(journal.len(), 4);
+    assert_eq!(journal.accounts(), BTreeSet::from([102, 501]));
+}
+
+#[test]
+fn reordered_journals_should_be_equivalent() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(20).unwrap());
+    journal.push(&accounts[2], Transaction::debit(30).unwrap());
+
+    let mut reordered = Journal::new(Utc.ymd(2005, 4, 23));
+    reordered.push(&accounts[2], Transaction::debit(30).unwrap());
+    reordered.push(&accounts[1], Transaction::credit(50).unwrap());
+    reordered.push(&accounts[2], Transaction::debit(20).unwrap());
+
+    let journal = journal.validate().unwrap();
+    let reordered = reordered.validate().unwrap();
+
+    assert_ne!(journal, reordered);
+    assert!(journal.equivalent(&reordered));
+}
+
+#[test]
+fn journals_with_different_amounts_should_not_be_equivalent() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    let mut other = Journal::new(Utc.ymd(2005, 4, 23));
+    other.push(&accounts[2], Transaction::debit(40).unwrap());
+    other.push(&accounts[1], Transaction::credit(40).unwrap());
+
+    assert!(!journal
+        .validate()
+        .unwrap()
+        .equivalent(&other.validate().unwrap()));
+}
+
+#[test]
+fn note_should_not_affect_balance_but_be_kept() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push_note(&accounts[0], "Reimbursed next month");
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    assert!(journal.is_balanced());
+    assert_eq!(journal.len(), 2);
+    assert_eq!(journal.notes()[0].text(), "Reimbursed next month");
+
+    let validated = journal.validate().unwrap();
+    let notes = validated
+        .notes()
+        .iter()
+        .map(|x| (x.account().number().number(), x.text()))
+        .collect::<Vec<_>>();
+    assert_eq!(notes, vec![(101, "Reimbursed next month")]);
+}
+
+#[test]
+fn lines_should_yield_notes_where_they_were_pushed() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push_note(&accounts[0], "Reimbursed next month");
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+    journal.remove(0);
+    journal.push_note(&accounts[2], "Weekly shopping");
+
+    let lines = journal
+        .lines()
+        .map(|line| match line {
+            JournalLine::Entry(_) => (line.account().number().number(), None),
+            JournalLine::Note(note) => (line.account().number().number(), Some(note.text())),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            (101, Some("Reimbursed next month")),
+            (501, None),
+            (501, Some("Weekly shopping")),
+        ]
+    );
+}
+
+fn is_note(line: JournalLine<'_>) -> bool {
+    matches!(line, JournalLine::Note(_))
+}
+
+#[test]
+fn pop_should_place_notes_like_removing_the_last_entry() {
+    let accounts = accounts();
+
+    let mut popped = Journal::new(Utc.ymd(2005, 4, 23));
+    popped.push(&accounts[1], Transaction::credit(50).unwrap());
+    popped.push(&accounts[2], Transaction::debit(50).unwrap());
+    popped.push_note(&accounts[2], "Weekly shopping");
+    let mut removed = popped.clone();
+
+    popped.pop();
+    popped.push(&accounts[2], Transaction::debit(40).unwrap());
+    removed.remove(1);
+    removed.push(&accounts[2], Transaction::debit(40).unwrap());
+
+    let lines = popped.lines().map(is_note).collect::<Vec<_>>();
+    assert_eq!(lines, vec![false, true, false]);
+    assert_eq!(lines, removed.lines().map(is_note).collect::<Vec<_>>());
+}
+
+#[test]
+fn removing_a_wrong_line_should_leave_the_others_in_order() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[0], Transaction::credit(50).unwrap());
+    journal.push(&accounts[1], Transaction::credit(20).unwrap());
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    let removed = journal.remove(1).unwrap();
+
+    assert_eq!(removed.account(), &accounts[1]);
+    assert_eq!(journal.len(), 2);
+    assert_eq!(journal.as_slice()[0].account(), &accounts[0]);
+    assert_eq!(journal.as_slice()[1].account(), &accounts[2]);
+    assert!(journal.remove(2).is_none());
+    assert!(journal.validate().is_ok());
+}
+
+#[test]
+fn popping_all_lines_should_leave_an_empty_journal() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(50).unwrap());
+
+    assert_eq!(journal.pop().unwrap().account(), &accounts[2]);
+    assert_eq!(journal.pop().unwrap().account(), &accounts[1]);
+    assert!(journal.pop().is_none());
+    assert!(journal.is_empty());
+}
+
+#[test]
+fn split_transaction_should_net_per_account() {
+    let accounts = accounts();
+
+    let mut journal = Journal::new(Utc.ymd(2005, 4, 23));
+
+    journal.push(&accounts[1], Transaction::credit(50).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+    journal.push(&accounts[2], Transaction::debit(30).unwrap());
+    journal.push(&accounts[2], Transaction::debit(10).unwrap());
+
+    let journal = journal.validate().unwrap();
+    let actual = journal.net_by_account();
+
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[&accounts[1]], -50);
+    assert_eq!(actual[&accounts[2]], 50);
+}